@@ -0,0 +1,94 @@
+use std::fmt::Debug;
+
+use criterion::*;
+use rand::prelude::random;
+
+use gtree_experiments::{*, klist::*, rank::*};
+
+fn random_items(n: usize) -> Vec<u32> {
+    let mut items = vec![];
+    for _ in 0..n {
+        items.push(random());
+    }
+    return items;
+}
+
+fn insert_persistent<S: NonemptySet<Item = u32> + Debug>(items: &[u32], ranker: &HashRank) -> GTree<S> {
+    let mut t = GTree::Empty;
+    for item in items {
+        t = insert_ranked(&t, *item, ranker);
+    }
+    return t;
+}
+
+fn insert_in_place<S: NonemptySet<Item = u32> + Debug>(items: &[u32], ranker: &HashRank) -> GTree<S> {
+    let mut t = GTree::Empty;
+    for item in items {
+        t.insert_mut(*item, ranker.rank(item));
+    }
+    return t;
+}
+
+fn remove_persistent<S: NonemptySet<Item = u32> + Debug>(mut t: GTree<S>, items: &[u32]) -> GTree<S> {
+    for item in items {
+        t = delete(&t, item);
+    }
+    return t;
+}
+
+fn remove_in_place<S: NonemptySet<Item = u32> + Debug>(mut t: GTree<S>, items: &[u32]) -> GTree<S> {
+    for item in items {
+        t.remove_mut(item);
+    }
+    return t;
+}
+
+pub fn bench_mutation(c: &mut Criterion) {
+    let plot_config = PlotConfiguration::default().summary_scale(AxisScale::Logarithmic);
+    let mut group = c.benchmark_group("Mutation");
+    group.plot_config(plot_config);
+    group.sample_size(20);
+
+    let ranker = HashRank::new(15);
+
+    for i in [1024, 4096, 16384, 65536].iter() {
+        group.bench_with_input(BenchmarkId::new("Insert persistent 15-Zip", i), i, |b, i| {
+            b.iter_batched(
+                || random_items(*i),
+                |items| insert_persistent::<NonemptyReverseKList<15, u32>>(&items, &ranker),
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("Insert in place 15-Zip", i), i, |b, i| {
+            b.iter_batched(
+                || random_items(*i),
+                |items| insert_in_place::<NonemptyReverseKList<15, u32>>(&items, &ranker),
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("Remove persistent 15-Zip", i), i, |b, i| {
+            b.iter_batched(
+                || {
+                    let items = random_items(*i);
+                    (insert_in_place::<NonemptyReverseKList<15, u32>>(&items, &ranker), items)
+                },
+                |(t, items)| remove_persistent(t, &items),
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("Remove in place 15-Zip", i), i, |b, i| {
+            b.iter_batched(
+                || {
+                    let items = random_items(*i);
+                    (insert_in_place::<NonemptyReverseKList<15, u32>>(&items, &ranker), items)
+                },
+                |(t, items)| remove_in_place(t, &items),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_mutation);
+criterion_main!(benches);
@@ -3,32 +3,26 @@ use std::fmt::Debug;
 
 use criterion::*;
 use rand::prelude::random;
-use rand_distr::{Geometric, Distribution};
 
-use gtree_experiments::{*, klist::*};
+use gtree_experiments::{*, klist::*, rank::*};
 
-fn create_gtree<S: NonemptySet<Item = u32> + Debug>(items: &[(u32, u8 /*rank*/)]) -> GTree<S> {
-    let mut t = GTree::Empty;
+fn create_gtree<S: NonemptySet<Item = u32> + Debug>(items: &[u32], ranker: &HashRank) -> GTree<S> {
+    let mut sorted = items.to_vec();
+    sorted.sort();
+    sorted.dedup();
 
-    for (item, rank) in items {
-        t = insert(&t, *item, *rank);
-    }
-
-    return t;
+    return GTree::from_sorted_ranked(sorted.into_iter().map(|item| (item, ranker.rank(&item))));
 }
 
 fn random_gtree_of_size<S: NonemptySet<Item = u32> + Debug>(n: usize, target_node_size: usize) -> GTree<S> {
     let mut items = vec![];
-    let geo = Geometric::new(1.0 - (1.0 / ((target_node_size + 1) as f64))).unwrap();
 
     for _ in 0..n {
         let key: u32 = random();
-        let rank = geo.sample(&mut rand::thread_rng()) as u8;
-
-        items.push((key, rank));
+        items.push(key);
     }
 
-    return create_gtree(&items[..]);
+    return create_gtree(&items[..], &HashRank::new(target_node_size));
 }
 
 fn setup<S: NonemptySet<Item = u32> + Debug>(n: usize, target_node_size: usize) -> (GTree<S>, Vec<u32>/* items to search for*/) {
@@ -203,5 +197,95 @@ pub fn bench_search(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_search);
+// The lookup that `has` performed before `NonemptySet::search_ref` existed: every step clones an item and a left subtree out of the set.
+fn has_by_value<S: NonemptySet>(t: &GTree<S>, key: &S::Item) -> bool where S::Item: Ord {
+    match t {
+        GTree::Empty => return false,
+        GTree::NonEmpty(node) => match node.set().search(key) {
+            None => return has_by_value(node.right(), key),
+            Some((item, left_subtree)) => {
+                if &item == key {
+                    return true;
+                } else {
+                    return has_by_value(&left_subtree, key);
+                }
+            }
+        },
+    }
+}
+
+fn setup_strings<S: NonemptySet<Item = String> + Debug>(n: usize, target_node_size: usize) -> (GTree<S>, Vec<String>/* items to search for*/) {
+    let ranker = HashRank::new(target_node_size);
+    let mut items: Vec<String> = (0..n).map(|_| format!("/home/alice/projects/{:08x}", random::<u32>())).collect();
+    items.sort();
+    items.dedup();
+    let tree = GTree::from_sorted_ranked(items.into_iter().map(|item| {
+        let rank = ranker.rank(&item);
+        return (item, rank);
+    }));
+
+    let mut keys = vec![];
+    for _ in 0..100 {
+        keys.push(format!("/home/alice/projects/{:08x}", random::<u32>()));
+    }
+    return (tree, keys);
+}
+
+// Compare searching by reference (`has`) with searching by value (`has_by_value`).
+pub fn bench_search_ref(c: &mut Criterion) {
+    let plot_config = PlotConfiguration::default().summary_scale(AxisScale::Logarithmic);
+    let mut group = c.benchmark_group("Search by reference");
+    group.plot_config(plot_config);
+    group.sample_size(20);
+
+    for i in [1024, 8192, 65536].iter() {
+        group.bench_with_input(BenchmarkId::new("By value 15-Zip", i), i, |b, i| {
+            b.iter_batched_ref(
+                || setup::<NonemptyReverseKList<15, u32>>(*i, 15),
+                |(tree, items)| {
+                    for key in items {
+                        has_by_value(tree, key);
+                    }
+                },
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("By reference 15-Zip", i), i, |b, i| {
+            b.iter_batched_ref(
+                || setup::<NonemptyReverseKList<15, u32>>(*i, 15),
+                |(tree, items)| {
+                    for key in items {
+                        has(tree, key);
+                    }
+                },
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("By value 15-Zip, String", i), i, |b, i| {
+            b.iter_batched_ref(
+                || setup_strings::<NonemptyReverseKList<15, String>>(*i, 15),
+                |(tree, items)| {
+                    for key in items {
+                        has_by_value(tree, key);
+                    }
+                },
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("By reference 15-Zip, &str", i), i, |b, i| {
+            b.iter_batched_ref(
+                || setup_strings::<NonemptyReverseKList<15, String>>(*i, 15),
+                |(tree, items)| {
+                    for key in items {
+                        has(tree, key.as_str());
+                    }
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_search, bench_search_ref);
 criterion_main!(benches);
@@ -0,0 +1,52 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::BTreeSet;
+
+use gtree_experiments::{*, algebra::*, klist::*, validate::*};
+
+type S = NonemptyReverseKList<3, u8>;
+
+// Build a tree by inserting the items one by one, at ranks derived from their hashes.
+fn from_scratch(items: &BTreeSet<u8>) -> GTree<S> {
+    let mut t = GTree::Empty;
+    for item in items {
+        t = t.insert(*item);
+    }
+    return t;
+}
+
+fn check(t: &GTree<S>, expected: &BTreeSet<u8>) {
+    assert!(t.iter().eq(expected.iter()));
+    assert_eq!(t.len(), expected.len());
+    validate(t).unwrap();
+}
+
+fuzz_target!(|data: (TreeCreation<u8>, TreeCreation<u8>)| {
+    let (creation_a, creation_b) = data;
+
+    // Trees with arbitrary ranks.
+    let a: GTree<S> = create_tree(creation_a.clone());
+    let b: GTree<S> = create_tree(creation_b.clone());
+    let ctrl_a = create_ctrl_tree(creation_a);
+    let ctrl_b = create_ctrl_tree(creation_b);
+
+    let union_ctrl: BTreeSet<u8> = ctrl_a.union(&ctrl_b).cloned().collect();
+    let intersection_ctrl: BTreeSet<u8> = ctrl_a.intersection(&ctrl_b).cloned().collect();
+    let difference_ctrl: BTreeSet<u8> = ctrl_a.difference(&ctrl_b).cloned().collect();
+    let symmetric_difference_ctrl: BTreeSet<u8> = ctrl_a.symmetric_difference(&ctrl_b).cloned().collect();
+
+    check(&union(&a, &b), &union_ctrl);
+    check(&intersection(&a, &b), &intersection_ctrl);
+    check(&difference(&a, &b), &difference_ctrl);
+    check(&symmetric_difference(&a, &b), &symmetric_difference_ctrl);
+
+    // With ranks derived from hashes, the results must have exactly the shape of trees built from scratch.
+    let a = from_scratch(&ctrl_a);
+    let b = from_scratch(&ctrl_b);
+
+    assert_eq!(union(&a, &b), from_scratch(&union_ctrl));
+    assert_eq!(intersection(&a, &b), from_scratch(&intersection_ctrl));
+    assert_eq!(difference(&a, &b), from_scratch(&difference_ctrl));
+    assert_eq!(symmetric_difference(&a, &b), from_scratch(&symmetric_difference_ctrl));
+});
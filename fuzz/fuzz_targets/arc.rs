@@ -0,0 +1,23 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use gtree_experiments::{*, klist::*};
+
+fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+fuzz_target!(|data: TreeCreation<u8>| {
+    let rc_tree: GTree<NonemptyReverseKList<3, u8>> = create_tree(data.clone());
+    let arc_tree: GTree<NonemptyReverseArcKList<3, u8>> = create_tree(data.clone());
+    let ctrl = create_ctrl_tree(data);
+
+    assert_send_sync(&arc_tree);
+
+    // Both pointer families must yield trees of the same shape, which also have the same debug representation.
+    assert_eq!(format!("{:?}", rc_tree), format!("{:?}", arc_tree));
+    assert!(arc_tree.content_eq(&rc_tree));
+    assert!(arc_tree.iter().eq(ctrl.iter()));
+
+    let shared = arc_tree.clone();
+    let from_thread = std::thread::spawn(move || shared.iter().cloned().collect::<Vec<_>>()).join().unwrap();
+    assert!(from_thread.iter().eq(ctrl.iter()));
+});
@@ -0,0 +1,45 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use gtree_experiments::{*, array::*, klist::*};
+
+fuzz_target!(|data: (SetCreationOperation<u8>, u8, TreeCreation<u8>)| {
+    let (set_creation, key, tree_creation) = data;
+
+    // The set operations must agree with the control set.
+    let ctrl: Option<Set<ControlSet<u8>>> = create_set(set_creation.clone());
+    if let Some(ctrl) = ctrl {
+        let array: Set<NonemptyReverseArray<3, u8>> = create_set(set_creation).unwrap();
+        possibly_empty_sets_assert_eq(&array, &ctrl);
+
+        if let (Set::NonEmpty(array), Set::NonEmpty(ctrl)) = (array, ctrl) {
+            assert_eq!(
+                array.search(&key).map(|(item, _)| item),
+                ctrl.search(&key).map(|(item, _)| item)
+            );
+
+            let (array_left, array_key, array_right) = array.split(&key);
+            let (ctrl_left, ctrl_key, ctrl_right) = ctrl.split(&key);
+            possibly_empty_sets_assert_eq(&array_left, &ctrl_left);
+            possibly_empty_sets_assert_eq(&array_right, &ctrl_right);
+            assert_eq!(array_key.is_some(), ctrl_key.is_some());
+
+            if let (Set::NonEmpty(array_left), Set::NonEmpty(array_right), None) = (array_left, array_right, array_key) {
+                sets_assert_eq(&NonemptyReverseArray::join(&array_left, &array_right), &ctrl);
+            }
+        }
+    }
+
+    // Trees of arrays must have the same items as the control tree, and the same G-nodes as trees of k-lists.
+    let array_tree: GTree<NonemptyReverseArray<3, u8>> = create_tree(tree_creation.clone());
+    let klist_tree: GTree<NonemptyReverseKList<3, u8>> = create_tree(tree_creation.clone());
+    let ctrl_tree = create_ctrl_tree(tree_creation);
+
+    assert!(array_tree.iter().eq(ctrl_tree.iter()));
+
+    let (array_stats, array_ranks) = gtree_stats(&array_tree);
+    let (klist_stats, klist_ranks) = gtree_stats(&klist_tree);
+    assert_eq!(array_stats.gnode_count, klist_stats.gnode_count);
+    assert_eq!(array_stats.gnode_height, klist_stats.gnode_height);
+    assert_eq!(array_ranks, klist_ranks);
+});
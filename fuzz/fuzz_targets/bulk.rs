@@ -0,0 +1,22 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::BTreeMap;
+
+use gtree_experiments::{*, klist::*, validate::*};
+
+fuzz_target!(|data: Vec<(u8, u8)>| {
+    // Later ranks for the same item overwrite earlier ones, both in the map and when inserting.
+    let mut ranks = BTreeMap::new();
+    let mut inserted: GTree<NonemptyReverseKList<3, u8>> = GTree::Empty;
+    for (item, rank) in data {
+        ranks.insert(item, rank);
+        inserted = insert(&inserted, item, rank);
+        validate(&inserted).unwrap();
+    }
+
+    let bulk: GTree<NonemptyReverseKList<3, u8>> = GTree::from_sorted_ranked(ranks.into_iter());
+
+    validate(&bulk).unwrap();
+    assert_eq!(bulk, inserted);
+});
@@ -0,0 +1,14 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use gtree_experiments::{array::*, klist::*, nested::*, packed::*, testing::*};
+
+fuzz_target!(|operations: Vec<ConformanceOperation<u8>>| {
+    check_conformance::<NonemptyReverseKList<1, u8>>(&operations);
+    check_conformance::<NonemptyReverseKList<2, u8>>(&operations);
+    check_conformance::<NonemptyReverseKList<3, u8>>(&operations);
+    check_conformance::<NonemptyReverseKList<15, u8>>(&operations);
+    check_conformance::<NonemptyReverseArray<3, u8>>(&operations);
+    check_conformance::<NonemptyNestedSet<3, 2, u8>>(&operations);
+    check_conformance::<NonemptyPackedSet<3, u8>>(&operations);
+});
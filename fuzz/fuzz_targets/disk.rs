@@ -0,0 +1,111 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::HashMap;
+
+use gtree_experiments::{*, disk::*, klist::*};
+
+type S = NonemptyReverseKList<3, u8>;
+
+// The pages of the G-nodes of an in-memory tree and of its file-backed counterpart, in both directions.
+#[derive(Default)]
+struct Pages {
+    by_node: HashMap<*const GTreeNode<S>, DiskTree>,
+    by_page: HashMap<DiskTree, *const GTreeNode<S>>,
+}
+
+// Check that `on_disk` has the same shape as `in_memory`: the same ranks, and G-nodes with the same items, split into vertices alike. G-nodes of `in_memory` must share their pages with G-nodes of the previous version of the tree (recorded in `previous`) exactly if they are shared in memory. Record the pages of `in_memory` in `current`.
+fn check_shape(store: &DiskStore<3, u8>, on_disk: DiskTree, in_memory: &GTree<S>, previous: &Pages, current: &mut Pages) {
+    let node = store.node(on_disk).unwrap();
+    let (node, gnode) = match (node, in_memory) {
+        (None, GTree::Empty) => return,
+        (Some(node), GTree::NonEmpty(gnode)) => (node, gnode),
+        (node, _) => panic!("different trees: {:?} on disk, {:?} in memory", node, in_memory),
+    };
+
+    let address = &**gnode as *const GTreeNode<S>;
+    match (previous.by_node.get(&address), previous.by_page.get(&on_disk)) {
+        (None, None) => {}
+        (Some(page), Some(shared)) if *page == on_disk && *shared == address => {}
+        _ => panic!("the trees share different G-nodes with their previous versions"),
+    }
+    if current.by_node.insert(address, on_disk).is_some() {
+        // G-nodes are only shared between versions, never within a tree.
+        panic!("G-node visited twice");
+    }
+    current.by_page.insert(on_disk, address);
+
+    assert_eq!(node.rank, gnode.rank());
+    let vertices: Vec<Vec<u8>> = node.vertices.iter().map(|vertex| vertex.iter().map(|(item, _)| *item).collect()).collect();
+    let in_memory_vertices: Vec<Vec<u8>> = gnode.set().vertex_chain().into_iter().map(|(_, items)| items.into_iter().cloned().collect()).collect();
+    assert_eq!(vertices, in_memory_vertices);
+
+    for ((_, left), (_, in_memory_left)) in node.vertices.iter().flatten().zip(gnode.set().pairs_descending()) {
+        check_shape(store, *left, in_memory_left, previous, current);
+    }
+    check_shape(store, node.right, gnode.right(), previous, current);
+}
+
+// Insert (with a rank) or remove an item, and optionally commit and reopen the file afterwards. The file-backed tree must agree with an in-memory tree.
+fuzz_target!(|data: Vec<(u8, Option<u8>, bool)>| {
+    let path = std::env::temp_dir().join(format!("gtree_fuzz_disk_{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut store = DiskStore::<3, u8>::open(&path, 8).unwrap();
+    let mut on_disk = store.root();
+    let mut in_memory: GTree<S> = GTree::Empty;
+    let mut committed = in_memory.clone();
+    let mut pages = Pages::default();
+
+    for (item, rank, reopen) in data {
+        // Keep the G-nodes of the previous version alive, so that their addresses in `pages` stay valid.
+        let previous = in_memory.clone();
+        let mut unchanged = false;
+        match rank {
+            Some(rank) => {
+                on_disk = store.insert(on_disk, item, rank).unwrap();
+                in_memory = insert(&in_memory, item, rank);
+            }
+            None => {
+                let before = on_disk;
+                on_disk = store.delete(on_disk, &item).unwrap();
+                // Unlike the in-memory `delete`, which copies the search path in any case, the store does not write any pages when the item is missing.
+                unchanged = !has(&in_memory, &item);
+                if unchanged {
+                    assert_eq!(on_disk, before);
+                }
+                in_memory = delete(&in_memory, &item);
+            }
+        }
+
+        assert_eq!(store.has(on_disk, &item).unwrap(), has(&in_memory, &item));
+        assert_eq!(store.items(on_disk).unwrap(), in_memory.iter().cloned().collect::<Vec<_>>());
+        if unchanged {
+            pages = Pages::default();
+        }
+        let mut current = Pages::default();
+        check_shape(&store, on_disk, &in_memory, &pages, &mut current);
+        pages = current;
+        drop(previous);
+
+        if reopen {
+            // Commit only every other time, so that reopening also discards uncommitted updates.
+            if item % 2 == 0 {
+                store.commit(on_disk).unwrap();
+                committed = in_memory.clone();
+            }
+            drop(store);
+            store = DiskStore::open(&path, 8).unwrap();
+            on_disk = store.root();
+            in_memory = committed.clone();
+            assert_eq!(store.items(on_disk).unwrap(), in_memory.iter().cloned().collect::<Vec<_>>());
+
+            // The committed tree may be older than the previous version, so we start recording pages afresh.
+            pages = Pages::default();
+            check_shape(&store, on_disk, &in_memory, &Pages::default(), &mut pages);
+        }
+    }
+
+    drop(store);
+    let _ = std::fs::remove_file(&path);
+});
@@ -0,0 +1,52 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::ops::Bound;
+
+use gtree_experiments::{*, augment::*, klist::*};
+
+// Summarizes items as the sequence of all items, which makes every mistake in the order or coverage of the summaries visible.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Items(Vec<u8>);
+
+impl Monoid for Items {
+    fn neutral() -> Self {
+        return Items(vec![]);
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        let mut items = self.0.clone();
+        items.extend_from_slice(&other.0);
+        return Items(items);
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Concat;
+
+impl Augmentation<u8> for Concat {
+    type Summary = Items;
+
+    fn summarize(item: &u8) -> Items {
+        return Items(vec![*item]);
+    }
+}
+
+fuzz_target!(|data_: (TreeCreation<u8>, Bound<u8>, Bound<u8>)| {
+    let (data, lower, upper) = data_;
+
+    // BTreeSet::range panics if the range starts after its end, or if both bounds are the same excluded item.
+    match (lower, upper) {
+        (Bound::Included(l) | Bound::Excluded(l), Bound::Included(u) | Bound::Excluded(u)) if l > u => return,
+        (Bound::Excluded(l), Bound::Excluded(u)) if l == u => return,
+        _ => {}
+    }
+
+    let gtree: GTree<NonemptyReverseKList<3, u8, Concat>> = create_tree(data.clone());
+    let ctrl = create_ctrl_tree(data);
+
+    let range = (lower, upper);
+
+    assert_eq!(gtree.summary(), Items(ctrl.iter().cloned().collect()));
+    assert_eq!(gtree.fold_range(range), Items(ctrl.range(range).cloned().collect()));
+});
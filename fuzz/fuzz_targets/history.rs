@@ -0,0 +1,55 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::BTreeSet;
+
+use gtree_experiments::{*, klist::*, rank::*, validate::*};
+
+type S = NonemptyReverseKList<3, u8>;
+
+fuzz_target!(|data: (Vec<u8>, Vec<u8>)| {
+    let (inserted, deleted) = data;
+
+    // Insert and delete in the given order.
+    let mut t: GTree<S> = GTree::Empty;
+    for item in inserted.iter() {
+        t = t.insert(*item);
+        validate(&t).unwrap();
+    }
+    for item in deleted.iter() {
+        t = delete(&t, item);
+        validate(&t).unwrap();
+    }
+
+    let mut ctrl: BTreeSet<u8> = inserted.iter().cloned().collect();
+    for item in deleted.iter() {
+        ctrl.remove(item);
+    }
+
+    // Insert only the remaining items, once in descending and once in ascending order.
+    let mut descending: GTree<S> = GTree::Empty;
+    for item in ctrl.iter().rev() {
+        descending = descending.insert(*item);
+    }
+    let mut ascending: GTree<S> = GTree::Empty;
+    for item in ctrl.iter() {
+        ascending = ascending.insert(*item);
+    }
+
+    assert_eq!(t, descending);
+    assert_eq!(t, ascending);
+    assert!(t.content_eq(&ascending));
+
+    // Trees with the same items have the same content, even if their ranks and inner sets differ. They only have the same shape if the ranks agree.
+    let mut rank_zero: GTree<S> = GTree::Empty;
+    let mut ctrl_set: GTree<ControlSet<u8>> = GTree::Empty;
+    for item in ctrl.iter() {
+        rank_zero = insert(&rank_zero, *item, 0);
+        ctrl_set = insert(&ctrl_set, *item, 0);
+    }
+    assert!(t.content_eq(&rank_zero));
+    assert!(t.content_eq(&ctrl_set));
+
+    let ranker = HashRank::new(3);
+    assert_eq!(t == rank_zero, ctrl.iter().all(|item| ranker.rank(item) == 0));
+});
@@ -0,0 +1,24 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use gtree_experiments::{*, klist::*};
+
+fuzz_target!(|data_: (TreeCreation<u8>, Vec<bool>)| {
+    let (data, from_front) = data_;
+    let gtree: GTree<NonemptyReverseKList<3, u8>> = create_tree(data.clone());
+    let ctrl = create_ctrl_tree(data);
+
+    assert!(gtree.iter().eq(ctrl.iter()));
+
+    // Alternate between both ends as instructed by `from_front`, then drain from the front.
+    let mut iter = gtree.iter();
+    let mut ctrl_iter = ctrl.iter();
+    for front in from_front {
+        if front {
+            assert_eq!(iter.next(), ctrl_iter.next());
+        } else {
+            assert_eq!(iter.next_back(), ctrl_iter.next_back());
+        }
+    }
+    assert!(iter.eq(ctrl_iter));
+});
@@ -3,7 +3,7 @@ use libfuzzer_sys::fuzz_target;
 
 use std::collections::HashSet;
 
-use gtree_experiments::{klist::*, *};
+use gtree_experiments::{klist::*, packed::*, *};
 
 fuzz_target!(|data_: (&[u8], usize)| {
     let (data, split) = data_;
@@ -34,8 +34,13 @@ fuzz_target!(|data_: (&[u8], usize)| {
 
     let klist = NonemptyReverseKList::join(&klist2, &klist1);
 
+    let packed1: NonemptyPackedSet<3, u8> = NonemptyPackedSet::from_descending(&v1);
+    let packed2: NonemptyPackedSet<3, u8> = NonemptyPackedSet::from_descending(&v2);
+    let packed = NonemptyPackedSet::join(&packed2, &packed1);
+
     // let klist: NonemptyReverseKList<3, u8> = NonemptyReverseKList::from_descending(&v);
     let ctrl = ControlSet(v.iter().map(|x| (*x, GTree::Empty)).collect());
 
     sets_assert_eq(&klist, &ctrl);
+    sets_assert_eq(&packed, &ctrl);
 });
@@ -0,0 +1,32 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use gtree_experiments::{*, klist::*, map::*};
+
+fuzz_target!(|data: MapCreation<u8, u16>| {
+    let klist_map: GTreeMap<NonemptyReverseKListMap<3, u8, u16>> = create_map(data.clone());
+    let ctrl_map: GTreeMap<ControlMap<u8, u16>> = create_map(data.clone());
+    let ctrl = create_ctrl_map(data.clone());
+
+    for i in 0..=255 {
+        let got_klist = klist_map.get(&i);
+        let got_ctrl_map = ctrl_map.get(&i);
+        let got_ctrl = ctrl.get(&i);
+
+        if got_klist != got_ctrl || got_ctrl_map != got_ctrl {
+            println!("\n\nDifferent lookup results for {:?}.\n{:#?}\n{:#?}\n{:#?}", i, klist_map, ctrl_map, ctrl);
+        }
+
+        assert_eq!(got_klist, got_ctrl);
+        assert_eq!(got_ctrl_map, got_ctrl);
+    }
+
+    // Maps compare values, not just keys.
+    let same_klist_map: GTreeMap<NonemptyReverseKListMap<3, u8, u16>> = create_map(data);
+    assert!(klist_map == same_klist_map);
+    if let Some((key, value)) = ctrl.iter().next() {
+        let (changed, _) = klist_map.insert(*key, value.wrapping_add(1), 0);
+        assert!(changed != klist_map);
+        assert!(changed.insert(*key, *value, 0).0 == klist_map);
+    }
+});
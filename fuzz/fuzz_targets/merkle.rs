@@ -0,0 +1,64 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::BTreeMap;
+
+use gtree_experiments::{*, klist::*, pointer::RcFamily, testing::TestHasher, validate::*};
+
+type S = NonemptyReverseKList<3, u8, (), RcFamily, TestHasher>;
+
+// Build a tree from scratch, inserting in ascending order.
+fn rebuild(ctrl: &BTreeMap<u8, u8>) -> GTree<S> {
+    let mut t = GTree::Empty;
+    for (item, rank) in ctrl.iter() {
+        t = insert(&t, *item, *rank);
+    }
+    return t;
+}
+
+// Insert (with a rank) or remove an item.
+fuzz_target!(|data: Vec<(u8, Option<u8>)>| {
+    let mut persistent: GTree<S> = GTree::Empty;
+    let mut in_place: GTree<S> = GTree::Empty;
+    let mut ctrl = BTreeMap::new();
+    let mut snapshots = vec![];
+
+    for (item, rank) in data {
+        // Keep old versions around, so that some G-nodes are shared and must be copied rather than mutated.
+        snapshots.push((in_place.clone(), ctrl.clone(), persistent.root_hash()));
+
+        match rank {
+            Some(rank) => {
+                let rank = ctrl.get(&item).copied().unwrap_or(rank);
+                ctrl.insert(item, rank);
+                persistent = insert(&persistent, item, rank);
+                in_place.insert_mut(item, rank);
+            }
+            None => {
+                ctrl.remove(&item);
+                persistent = delete(&persistent, &item);
+                in_place.remove_mut(&item);
+            }
+        }
+
+        // Both trees must have kept the hashes cached in their G-nodes up to date.
+        validate(&persistent).unwrap();
+        validate(&in_place).unwrap();
+
+        // The root hash only depends on the items and their ranks.
+        let hash = persistent.root_hash();
+        assert_eq!(hash.is_none(), ctrl.is_empty());
+        assert_eq!(in_place.root_hash(), hash);
+        assert_eq!(rebuild(&ctrl).root_hash(), hash);
+
+        // Different contents have different hashes.
+        let (_, old_ctrl, old_hash) = snapshots.last().unwrap();
+        assert_eq!(*old_ctrl == ctrl, *old_hash == hash);
+    }
+
+    // Mutating must not have affected the hashes of the old versions.
+    for (old_in_place, old_ctrl, old_hash) in snapshots {
+        assert_eq!(old_in_place.root_hash(), old_hash);
+        assert_eq!(rebuild(&old_ctrl).root_hash(), old_hash);
+    }
+});
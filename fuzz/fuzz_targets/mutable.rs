@@ -0,0 +1,40 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use gtree_experiments::{*, klist::*, validate::*};
+
+type S = NonemptyReverseKList<3, u8>;
+
+// Insert (with a rank) or remove an item.
+fuzz_target!(|data: Vec<(u8, Option<u8>)>| {
+    let mut persistent: GTree<S> = GTree::Empty;
+    let mut in_place: GTree<S> = GTree::Empty;
+    let mut snapshots = vec![];
+
+    for (item, rank) in data {
+        // Keep old versions around, so that some G-nodes are shared and must be copied rather than mutated.
+        snapshots.push((in_place.clone(), persistent.clone()));
+
+        match rank {
+            Some(rank) => {
+                let is_new = !has(&persistent, &item);
+                persistent = insert(&persistent, item, rank);
+                assert_eq!(in_place.insert_mut(item, rank), is_new);
+            }
+            None => {
+                let was_present = has(&persistent, &item);
+                persistent = delete(&persistent, &item);
+                assert_eq!(in_place.remove_mut(&item), was_present);
+            }
+        }
+
+        validate(&in_place).unwrap();
+        assert_eq!(in_place, persistent);
+        assert_eq!(in_place.len(), persistent.len());
+    }
+
+    // Mutating must not have affected the old versions.
+    for (old_in_place, old_persistent) in snapshots {
+        assert_eq!(old_in_place, old_persistent);
+    }
+});
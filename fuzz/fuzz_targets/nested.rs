@@ -0,0 +1,45 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use gtree_experiments::{*, klist::*, nested::*};
+
+fuzz_target!(|data: (SetCreationOperation<u8>, u8, TreeCreation<u8>)| {
+    let (set_creation, key, tree_creation) = data;
+
+    // The set operations must agree with the control set.
+    let ctrl: Option<Set<ControlSet<u8>>> = create_set(set_creation.clone());
+    if let Some(ctrl) = ctrl {
+        let nested: Set<NonemptyNestedSet<3, 2, u8>> = create_set(set_creation).unwrap();
+        possibly_empty_sets_assert_eq(&nested, &ctrl);
+
+        if let (Set::NonEmpty(nested), Set::NonEmpty(ctrl)) = (nested, ctrl) {
+            assert_eq!(
+                nested.search(&key).map(|(item, _)| item),
+                ctrl.search(&key).map(|(item, _)| item)
+            );
+
+            let (nested_left, nested_key, nested_right) = nested.split(&key);
+            let (ctrl_left, ctrl_key, ctrl_right) = ctrl.split(&key);
+            possibly_empty_sets_assert_eq(&nested_left, &ctrl_left);
+            possibly_empty_sets_assert_eq(&nested_right, &ctrl_right);
+            assert_eq!(nested_key.is_some(), ctrl_key.is_some());
+
+            if let (Set::NonEmpty(nested_left), Set::NonEmpty(nested_right), None) = (nested_left, nested_right, nested_key) {
+                sets_assert_eq(&NonemptyNestedSet::join(&nested_left, &nested_right), &ctrl);
+            }
+        }
+    }
+
+    // Trees of nested sets must have the same items as the control tree, and the same G-nodes as trees of k-lists.
+    let nested_tree: GTree<NonemptyNestedSet<3, 2, u8>> = create_tree(tree_creation.clone());
+    let klist_tree: GTree<NonemptyReverseKList<3, u8>> = create_tree(tree_creation.clone());
+    let ctrl_tree = create_ctrl_tree(tree_creation);
+
+    assert!(nested_tree.iter().eq(ctrl_tree.iter()));
+
+    let (nested_stats, nested_ranks) = gtree_stats(&nested_tree);
+    let (klist_stats, klist_ranks) = gtree_stats(&klist_tree);
+    assert_eq!(nested_stats.gnode_count, klist_stats.gnode_count);
+    assert_eq!(nested_stats.gnode_height, klist_stats.gnode_height);
+    assert_eq!(nested_ranks, klist_ranks);
+});
@@ -0,0 +1,19 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use gtree_experiments::{*, klist::*};
+
+fuzz_target!(|data: TreeCreation<u8>| {
+    let gtree: GTree<NonemptyReverseKList<3, u8>> = create_tree(data.clone());
+    let ctrl = create_ctrl_tree(data);
+
+    assert_eq!(gtree.len(), ctrl.len());
+
+    for i in 0..=255 {
+        assert_eq!(gtree.count_less_than(&i), ctrl.range(..i).count());
+    }
+
+    for i in 0..=ctrl.len() {
+        assert_eq!(gtree.select(i), ctrl.iter().nth(i));
+    }
+});
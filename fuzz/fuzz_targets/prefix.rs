@@ -0,0 +1,45 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use gtree_experiments::{*, klist::*, prefix::*};
+
+fuzz_target!(|data: (SetCreationOperation<Vec<u8>>, Vec<u8>, TreeCreation<Vec<u8>>)| {
+    let (set_creation, key, tree_creation) = data;
+
+    // The set operations must agree with the control set.
+    let ctrl: Option<Set<ControlSet<Vec<u8>>>> = create_set(set_creation.clone());
+    if let Some(ctrl) = ctrl {
+        let prefix: Set<NonemptyPrefixSet<3>> = create_set(set_creation).unwrap();
+        possibly_empty_sets_assert_eq(&prefix, &ctrl);
+
+        if let (Set::NonEmpty(prefix), Set::NonEmpty(ctrl)) = (prefix, ctrl) {
+            assert_eq!(
+                prefix.search(&key).map(|(item, _)| item),
+                ctrl.search(&key).map(|(item, _)| item)
+            );
+
+            let (prefix_left, prefix_key, prefix_right) = prefix.split(&key);
+            let (ctrl_left, ctrl_key, ctrl_right) = ctrl.split(&key);
+            possibly_empty_sets_assert_eq(&prefix_left, &ctrl_left);
+            possibly_empty_sets_assert_eq(&prefix_right, &ctrl_right);
+            assert_eq!(prefix_key.is_some(), ctrl_key.is_some());
+
+            if let (Set::NonEmpty(prefix_left), Set::NonEmpty(prefix_right), None) = (prefix_left, prefix_right, prefix_key) {
+                sets_assert_eq(&NonemptyPrefixSet::join(&prefix_left, &prefix_right), &ctrl);
+            }
+        }
+    }
+
+    // Trees of prefix sets must have the same items as the control tree, and the same G-nodes as trees of k-lists.
+    let prefix_tree: GTree<NonemptyPrefixSet<3>> = create_tree(tree_creation.clone());
+    let klist_tree: GTree<NonemptyReverseKList<3, Vec<u8>>> = create_tree(tree_creation.clone());
+    let ctrl_tree = create_ctrl_tree(tree_creation);
+
+    assert!(prefix_tree.iter().eq(ctrl_tree.iter()));
+
+    let (prefix_stats, prefix_ranks) = gtree_stats(&prefix_tree);
+    let (klist_stats, klist_ranks) = gtree_stats(&klist_tree);
+    assert_eq!(prefix_stats.gnode_count, klist_stats.gnode_count);
+    assert_eq!(prefix_stats.gnode_height, klist_stats.gnode_height);
+    assert_eq!(prefix_ranks, klist_ranks);
+});
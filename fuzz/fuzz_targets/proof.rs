@@ -0,0 +1,37 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use gtree_experiments::{*, klist::*, merkle::*, pointer::RcFamily, testing::TestHasher};
+
+type S = NonemptyReverseKList<3, u8, (), RcFamily, TestHasher>;
+
+fuzz_target!(|data: TreeCreation<u8>| {
+    let gtree: GTree<S> = create_tree(data.clone());
+    let ctrl = create_ctrl_tree(data);
+    let root_hash = gtree.root_hash();
+
+    for i in 0..=255 {
+        let proof = prove(&gtree, &i);
+        assert_eq!(proof.contains, ctrl.contains(&i));
+        assert!(verify(root_hash.as_ref(), &i, &proof));
+
+        // The proof does not prove the opposite.
+        let mut flipped = proof.clone();
+        flipped.contains = !flipped.contains;
+        assert!(!verify(root_hash.as_ref(), &i, &flipped));
+
+        // The proof needs all of its G-nodes.
+        if !proof.path.is_empty() {
+            let mut truncated = proof.clone();
+            truncated.path.pop();
+            assert!(!verify(root_hash.as_ref(), &i, &truncated));
+        }
+
+        // The proof is not valid for a tree with a different root hash, such as the tree with or without `i`.
+        let other = if proof.contains { delete(&gtree, &i) } else { insert(&gtree, i, 0) };
+        assert!(!verify(other.root_hash().as_ref(), &i, &proof));
+        let other_proof = prove(&other, &i);
+        assert_eq!(other_proof.contains, !proof.contains);
+        assert!(verify(other.root_hash().as_ref(), &i, &other_proof));
+    }
+});
@@ -0,0 +1,27 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::ops::Bound;
+
+use gtree_experiments::{*, klist::*};
+
+fuzz_target!(|data_: (TreeCreation<u8>, Bound<u8>, Bound<u8>)| {
+    let (data, lower, upper) = data_;
+
+    // BTreeSet::range panics if the range starts after its end, or if both bounds are the same excluded item.
+    match (lower, upper) {
+        (Bound::Included(l) | Bound::Excluded(l), Bound::Included(u) | Bound::Excluded(u)) if l > u => return,
+        (Bound::Excluded(l), Bound::Excluded(u)) if l == u => return,
+        _ => {}
+    }
+
+    let gtree: GTree<NonemptyReverseKList<3, u8>> = create_tree(data.clone());
+    let ctrl = create_ctrl_tree(data);
+
+    let range = (lower, upper);
+
+    assert!(gtree.range(range).eq(ctrl.range(range)));
+    assert!(gtree.range(range).rev().eq(ctrl.range(range).rev()));
+    assert_eq!(gtree.first_in_range(range), ctrl.range(range).next());
+    assert_eq!(gtree.last_in_range(range), ctrl.range(range).next_back());
+});
@@ -3,7 +3,7 @@ use libfuzzer_sys::fuzz_target;
 
 use std::collections::HashSet;
 
-use gtree_experiments::{klist::*, *};
+use gtree_experiments::{klist::*, packed::*, *};
 
 fuzz_target!(|data_: (&[u8], u8)| {
     let (data, key) = data_;
@@ -20,6 +20,7 @@ fuzz_target!(|data_: (&[u8], u8)| {
     v.sort_by(|a, b| b.cmp(a));
 
     let klist: NonemptyReverseKList<3, u8> = NonemptyReverseKList::from_descending(&v);
+    let packed: NonemptyPackedSet<3, u8> = NonemptyPackedSet::from_descending(&v);
 
     let ctrl = ControlSet(v.iter().map(|x| (*x, GTree::Empty)).collect());
 
@@ -28,6 +29,9 @@ fuzz_target!(|data_: (&[u8], u8)| {
 
     assert_eq!(found, ctrl_found);
 
+    let packed_found = packed.search(&key).map(|(item, _subtree)| item.clone());
+    assert_eq!(packed_found, ctrl_found);
+
     // println!("\n\nsplit: {:#?}", split);
     // println!("\nklist: {:#?}\n", klist);
     // println!("\n\nv: {:#?}", v);
@@ -3,7 +3,7 @@ use libfuzzer_sys::fuzz_target;
 
 use std::collections::HashSet;
 
-use gtree_experiments::{klist::*, *};
+use gtree_experiments::{klist::*, packed::*, *};
 
 fuzz_target!(|data_: (&[u8], u8)| {
     let (data, split) = data_;
@@ -38,6 +38,9 @@ fuzz_target!(|data_: (&[u8], u8)| {
 
     let (klist1, _, klist2) = NonemptyReverseKList::split(&klist, &split);
 
+    let packed: NonemptyPackedSet<3, u8> = NonemptyPackedSet::from_descending(&v);
+    let (packed1, _, packed2) = NonemptyPackedSet::split(&packed, &split);
+
     let ctrl1_ = ControlSet(v1.iter().map(|x| (*x, GTree::Empty)).collect());
     let ctrl1 = if ctrl1_.0.len() == 0 {Set::Empty} else {Set::NonEmpty(ctrl1_)};
     let ctrl2_ = ControlSet(v2.iter().map(|x| (*x, GTree::Empty)).collect());
@@ -51,4 +54,6 @@ fuzz_target!(|data_: (&[u8], u8)| {
 
     possibly_empty_sets_assert_eq(&klist1, &ctrl1);
     possibly_empty_sets_assert_eq(&klist2, &ctrl2);
+    possibly_empty_sets_assert_eq(&packed1, &ctrl1);
+    possibly_empty_sets_assert_eq(&packed2, &ctrl2);
 });
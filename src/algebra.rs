@@ -0,0 +1,132 @@
+// This file implements set algebra on G-trees: union, intersection, difference and symmetric difference.
+//
+// All four operations follow the same recursive scheme. Of the two trees, we pick the one whose root has the greater rank, and use the middle item of that root as a pivot: the lesser items of the root (together with the left subtree of the pivot) hold all lesser items of the tree, and the greater items of the root (together with the right subtree) hold all greater items. We unzip the other tree at the pivot, recursively combine the lesser parts and the greater parts, and zip the two results back together, with the pivot in between if it belongs to the result.
+//
+// Whenever one side is empty (or both sides are the very same G-node), the result is decided without looking at the items: it is either empty or one of the two sides, which is then reused as a whole rather than copied.
+//
+// The shape of a G-tree is determined by its items and their ranks, so the results are identical to the trees obtained by inserting all resulting items with their ranks one by one. When both trees contain an item, the result uses the item and rank from the left tree.
+
+use std::fmt::Debug;
+
+use crate::pointer::PointerFamily;
+use crate::{lift, split_with_item, unzip_find, zip2, zip3, GTree, GTreeNode, NonemptySet};
+
+#[derive(Debug, Clone, Copy)]
+enum Operation {
+    Union,
+    Intersection,
+    Difference,
+    SymmetricDifference,
+}
+
+impl Operation {
+    // Whether an item belongs to the result, depending on whether it is in the left tree and whether it is in the right tree.
+    fn keeps(self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            Operation::Union => return in_left || in_right,
+            Operation::Intersection => return in_left && in_right,
+            Operation::Difference => return in_left && !in_right,
+            Operation::SymmetricDifference => return in_left != in_right,
+        }
+    }
+}
+
+/// Return a tree containing all items that are in `left` or in `right`.
+pub fn union<S: NonemptySet + Debug>(left: &GTree<S>, right: &GTree<S>) -> GTree<S>
+where
+    S::Item: Ord,
+{
+    return combine(Operation::Union, left, right);
+}
+
+/// Return a tree containing all items that are both in `left` and in `right`.
+pub fn intersection<S: NonemptySet + Debug>(left: &GTree<S>, right: &GTree<S>) -> GTree<S>
+where
+    S::Item: Ord,
+{
+    return combine(Operation::Intersection, left, right);
+}
+
+/// Return a tree containing all items that are in `left` but not in `right`.
+pub fn difference<S: NonemptySet + Debug>(left: &GTree<S>, right: &GTree<S>) -> GTree<S>
+where
+    S::Item: Ord,
+{
+    return combine(Operation::Difference, left, right);
+}
+
+/// Return a tree containing all items that are in exactly one of `left` and `right`.
+pub fn symmetric_difference<S: NonemptySet + Debug>(left: &GTree<S>, right: &GTree<S>) -> GTree<S>
+where
+    S::Item: Ord,
+{
+    return combine(Operation::SymmetricDifference, left, right);
+}
+
+// Split a G-node at the middle item of its set: return the tree of all lesser items, the middle item with its rank, and the tree of all greater items.
+// Splitting at the middle rather than at the least item means that the items of a G-node of size m are used as pivots in O(log m) levels of recursion, each of which builds G-nodes of half the size of the previous one, so a G-node costs O(m log m) rather than O(m^2) work.
+fn split_middle<S: NonemptySet>(node: &GTreeNode<S>) -> (GTree<S>, (S::Item, u8), GTree<S>) {
+    let pairs: Vec<_> = node.set.pairs_descending_lent().collect();
+    let (middle_item, _) = &pairs[pairs.len() / 2];
+    let (less, found, greater) = split_with_item(&node.set, middle_item);
+    let (pivot, left_subtree) = found.unwrap(/* the item is in the set */);
+    return (
+        lift(less, left_subtree, node.rank),
+        (pivot, node.rank),
+        lift(greater, node.right.clone(), node.rank),
+    );
+}
+
+fn combine<S: NonemptySet + Debug>(op: Operation, left: &GTree<S>, right: &GTree<S>) -> GTree<S>
+where
+    S::Item: Ord,
+{
+    match (left, right) {
+        (GTree::Empty, GTree::Empty) => return GTree::Empty,
+        (GTree::Empty, _) => {
+            if op.keeps(false, true) {
+                return right.clone();
+            } else {
+                return GTree::Empty;
+            }
+        }
+        (_, GTree::Empty) => {
+            if op.keeps(true, false) {
+                return left.clone();
+            } else {
+                return GTree::Empty;
+            }
+        }
+        (GTree::NonEmpty(l), GTree::NonEmpty(r)) => {
+            if S::Pointers::ptr_eq(l, r) {
+                // Both trees contain exactly the same items.
+                if op.keeps(true, true) {
+                    return left.clone();
+                } else {
+                    return GTree::Empty;
+                }
+            }
+
+            let (l_less, l_pivot, l_greater, r_less, r_pivot, r_greater);
+            if l.rank >= r.rank {
+                let (less, pivot, greater) = split_middle(l);
+                (r_less, r_pivot, r_greater) = unzip_find(right, &pivot.0);
+                (l_less, l_pivot, l_greater) = (less, Some(pivot), greater);
+            } else {
+                let (less, pivot, greater) = split_middle(r);
+                (l_less, l_pivot, l_greater) = unzip_find(left, &pivot.0);
+                (r_less, r_pivot, r_greater) = (less, Some(pivot), greater);
+            }
+
+            let less = combine(op, &l_less, &r_less);
+            let greater = combine(op, &l_greater, &r_greater);
+
+            if op.keeps(l_pivot.is_some(), r_pivot.is_some()) {
+                let (pivot, rank) = l_pivot.or(r_pivot).unwrap(/* at least one tree contains the pivot */);
+                return zip3(&less, pivot, rank, &greater);
+            } else {
+                return zip2(&less, &greater);
+            }
+        }
+    }
+}
@@ -0,0 +1,227 @@
+// This file implements an inner set that stores all its pairs in a single contiguous buffer.
+//
+// Where a k-list walks a chain of vertices of K pairs each, the array set keeps all pairs of a G-node in one `Vec`, sorted in descending order. Storing the pairs in reverse puts the least item at the end of the buffer, so `insert_min` and `remove_min` are a `push` and a `pop` (after copying the buffer, as the borrowing operations on inner sets are persistent; the consuming variants reuse it). `search`, `split` and `update_left_subtree` binary-search across the whole G-node rather than vertex by vertex, and `len` and `get_pair_by_index` take constant time.
+//
+// The const parameter K plays no role in the layout of the buffer; it only determines the rank distribution (via `TARGET_NODE_SIZE`), so that G-trees of arrays and of k-lists with the same K have the same shape.
+
+use std::{borrow::Borrow, cmp::Ordering, fmt::Debug, hash::{Hash, Hasher}, marker::PhantomData};
+
+use crate::{Set, GTree, NonemptySet, NonemptySetMeta, augment::Augmentation, map::KeyValue, pointer::{PointerFamily, RcFamily}};
+
+/// A non-empty set of item-left_subtree pairs, stored in a single buffer in descending order.
+#[derive(Clone)]
+pub struct NonemptyReverseArray<const K: usize, I: Clone + Ord + Debug, A: Augmentation<I> = (), P: PointerFamily = RcFamily> {
+    data: Vec<(I, GTree<Self>)>,
+    parameters: PhantomData<(A, P)>,
+}
+
+impl<const K: usize, I: Clone + Ord + Debug, A: Augmentation<I>, P: PointerFamily> NonemptyReverseArray<K, I, A, P> {
+    // Internal helper function: create a set from a non-empty buffer of pairs in descending order.
+    fn new(data: Vec<(I, GTree<Self>)>) -> Self {
+        return NonemptyReverseArray {
+            data,
+            parameters: PhantomData,
+        };
+    }
+
+    // Internal helper function: wrap a possibly empty buffer of pairs in descending order.
+    fn new_set(data: Vec<(I, GTree<Self>)>) -> Set<Self> {
+        if data.is_empty() {
+            return Set::Empty;
+        } else {
+            return Set::NonEmpty(NonemptyReverseArray::new(data));
+        }
+    }
+
+    // Internal helper function: the index of the least item that is greater than or equal to the key, if any.
+    // `cmp` is called with a stored item and must return how the key compares to that item.
+    fn locate_by<F: Fn(&I) -> Ordering>(&self, cmp: &F) -> Option<usize> {
+        // Because we store items in reverse, the comparison is reversed as well.
+        match self.data.binary_search_by(|(my_item, _)| cmp(my_item)) {
+            Ok(i) => return Some(i),
+            Err(i) => return i.checked_sub(1),
+        }
+    }
+
+    // Internal helper function: the implementation of `search`, but with the comparison against the key supplied as a function.
+    fn search_by<F: Fn(&I) -> Ordering>(&self, cmp: &F) -> Option<(I, GTree<Self>)> {
+        return self.locate_by(cmp).map(|i| self.data[i].clone());
+    }
+}
+
+impl<const K: usize, I: Clone + Ord + Debug, A: Augmentation<I>, P: PointerFamily> Debug for NonemptyReverseArray<K, I, A, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return f.debug_struct("NonemptyReverseArray").field("data", &self.data).finish();
+    }
+}
+
+impl<const K: usize, I: Clone + Ord + Debug, A: Augmentation<I>, P: PointerFamily> PartialEq for NonemptyReverseArray<K, I, A, P> {
+    fn eq(&self, other: &Self) -> bool {
+        return self.data == other.data;
+    }
+}
+
+impl<const K: usize, I: Clone + Ord + Debug, A: Augmentation<I>, P: PointerFamily> Eq for NonemptyReverseArray<K, I, A, P> {}
+
+impl<const K: usize, I: Clone + Ord + Debug + Hash, A: Augmentation<I>, P: PointerFamily> Hash for NonemptyReverseArray<K, I, A, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+    }
+}
+
+impl<const K: usize, I: Clone + Ord + Debug, A: Augmentation<I>, P: PointerFamily> NonemptySet for NonemptyReverseArray<K, I, A, P> {
+    type Item = I;
+    type Pointers = P;
+    type Augmentation = A;
+    type Hasher = ();
+
+    const TARGET_NODE_SIZE: usize = K;
+
+    fn singleton(item: (Self::Item, GTree<Self>)) -> Self {
+        return NonemptyReverseArray::new(vec![item]);
+    }
+
+    fn insert_min(&self, new_min: (Self::Item, GTree<Self>)) -> Self {
+        let mut data = Vec::with_capacity(self.data.len() + 1);
+        data.extend_from_slice(&self.data);
+        data.push(new_min);
+        return NonemptyReverseArray::new(data);
+    }
+
+    fn remove_min(&self) -> ((Self::Item, GTree<Self>), Set<Self>) {
+        let (min, others) = self.data.split_last().unwrap(/* the set is never empty */);
+        return (min.clone(), NonemptyReverseArray::new_set(others.to_vec()));
+    }
+
+    fn split(&self, key: &Self::Item) -> (Set<Self>, Option<GTree<Self>> /* left subtree of key (if key is in self, else None) */, Set<Self>) {
+        match self.data.binary_search_by(|(my_item, _)| key.cmp(my_item)) {
+            Ok(i) => {
+                // The first i items are greater than the key, the items after index i are less than the key.
+                return (
+                    NonemptyReverseArray::new_set(self.data[i + 1..].to_vec()),
+                    Some(self.data[i].1.clone()),
+                    NonemptyReverseArray::new_set(self.data[..i].to_vec()),
+                );
+            }
+            Err(i) => {
+                return (
+                    NonemptyReverseArray::new_set(self.data[i..].to_vec()),
+                    None,
+                    NonemptyReverseArray::new_set(self.data[..i].to_vec()),
+                );
+            }
+        }
+    }
+
+    fn join(left: &Self, right: &Self) -> Self {
+        // The greater items of `right` come first, because we store things in reverse order.
+        let mut data = Vec::with_capacity(left.data.len() + right.data.len());
+        data.extend_from_slice(&right.data);
+        data.extend_from_slice(&left.data);
+        return NonemptyReverseArray::new(data);
+    }
+
+    fn split_owned(mut self, key: &Self::Item) -> (Set<Self>, Option<(Self::Item, GTree<Self>)>, Set<Self>) {
+        match self.data.binary_search_by(|(my_item, _)| key.cmp(my_item)) {
+            Ok(i) => {
+                let left = self.data.split_off(i + 1);
+                let found = self.data.pop();
+                return (NonemptyReverseArray::new_set(left), found, NonemptyReverseArray::new_set(self.data));
+            }
+            Err(i) => {
+                let left = self.data.split_off(i);
+                return (NonemptyReverseArray::new_set(left), None, NonemptyReverseArray::new_set(self.data));
+            }
+        }
+    }
+
+    fn join_owned(left: Self, mut right: Self) -> Self {
+        right.data.extend(left.data);
+        return right;
+    }
+
+    fn remove_min_owned(mut self) -> ((Self::Item, GTree<Self>), Set<Self>) {
+        let min = self.data.pop().unwrap(/* the set is never empty */);
+        return (min, NonemptyReverseArray::new_set(self.data));
+    }
+
+    fn insert_min_owned(mut self, new_min: (Self::Item, GTree<Self>)) -> Self {
+        self.data.push(new_min);
+        return self;
+    }
+
+    fn search(&self, key: &Self::Item) -> Option<(Self::Item, GTree<Self>)> {
+        return self.search_by(&|my_item| key.cmp(my_item));
+    }
+
+    fn search_ref<Q: Ord + ?Sized>(&self, key: &Q) -> Option<(&Self::Item, &GTree<Self>)>
+    where
+        Self::Item: Borrow<Q>,
+    {
+        return self.locate_by(&|my_item: &I| key.cmp(my_item.borrow())).map(|i| (&self.data[i].0, &self.data[i].1));
+    }
+
+    fn pairs_descending(&self) -> impl Iterator<Item = (&Self::Item, &GTree<Self>)> {
+        return self.data.iter().map(|(item, subtree)| (item, subtree));
+    }
+
+    fn pairs_descending_from<Q: Ord + ?Sized>(&self, key: &Q) -> impl Iterator<Item = (&Self::Item, &GTree<Self>)>
+    where
+        Self::Item: Borrow<Q>,
+    {
+        let start = self.locate_by(&|my_item: &I| key.cmp(my_item.borrow())).unwrap_or(0);
+        return self.data[start..].iter().map(|(item, subtree)| (item, subtree));
+    }
+
+    fn update_left_subtree<F: FnOnce(&mut GTree<Self>)>(&mut self, key: &Self::Item, f: F) -> bool {
+        match self.locate_by(&|my_item| key.cmp(my_item)) {
+            None => return false,
+            Some(i) => {
+                f(&mut self.data[i].1);
+                return true;
+            }
+        }
+    }
+
+    fn from_descending_pairs(mut pairs: Vec<(Self::Item, GTree<Self>)>) -> Self {
+        assert!(!pairs.is_empty(), "Cannot create a set from zero pairs.");
+        pairs.shrink_to_fit();
+        return NonemptyReverseArray::new(pairs);
+    }
+}
+
+impl<const K: usize, I: Clone + Ord + Debug, A: Augmentation<I>, P: PointerFamily> NonemptySetMeta for NonemptyReverseArray<K, I, A, P> {
+    /// Return a reference to the maximal item in the set.
+    fn get_max(&self) -> &Self::Item {
+        return &self.data[0].0;
+    }
+
+    /// Return a reference to the minimal item in the set.
+    fn get_min(&self) -> &Self::Item {
+        return &self.data[self.data.len() - 1].0;
+    }
+
+    fn len(&self) -> usize {
+        return self.data.len();
+    }
+
+    fn get_pair_by_index(&self, index: usize) -> Option<(&Self::Item, &GTree<Self>)> {
+        return self.data.len().checked_sub(index + 1).map(|i| (&self.data[i].0, &self.data[i].1));
+    }
+
+    fn from_descending(items: &[Self::Item]) -> Self {
+        return NonemptyReverseArray::new(items.iter().map(|item| (item.clone(), GTree::Empty)).collect());
+    }
+
+    // The slots are those of the buffer, including any spare capacity.
+    fn item_slot_count(&self) -> usize {
+        return self.data.capacity();
+    }
+
+    fn heap_bytes(&self) -> usize {
+        return self.data.capacity() * std::mem::size_of::<(I, GTree<Self>)>();
+    }
+}
+
+/// An array set that can be used as the inner set of a `GTreeMap`.
+pub type NonemptyReverseArrayMap<const K: usize, Key, Value> = NonemptyReverseArray<K, KeyValue<Key, Value>>;
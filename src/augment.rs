@@ -0,0 +1,164 @@
+// This file implements monoid-augmented G-trees.
+//
+// An `Augmentation` maps every item to a summary, and summaries form a monoid. Every G-node caches the combined summary of all items in the tree it roots, and inner sets may additionally cache summaries for parts of themselves (k-lists do so per vertex). Since G-nodes are only ever created through `GTreeNode::new`, all operations that rebuild G-nodes (unzip, zip2, lift, update_leftmost, ...) keep the cached summaries up to date for free.
+//
+// `GTree::fold_range` answers aggregate queries over ranges. Like a range search, it descends along the two paths towards the bounds of the range. Every subtree that lies completely within the range contributes its cached summary, and within each G-node, the pairs between the two paths are summarized by `NonemptySet::fold_pairs`.
+
+use std::fmt::Debug;
+use std::ops::{Bound, RangeBounds};
+
+use crate::{above_lower, below_upper, GTree, NonemptySet};
+
+/// A type with an associative `combine` operation and a neutral element.
+pub trait Monoid: Clone + Debug {
+    fn neutral() -> Self;
+    /// Combine the summary of some items with the summary of greater items. Must be associative, but need not be commutative.
+    fn combine(&self, other: &Self) -> Self;
+}
+
+impl Monoid for () {
+    fn neutral() -> Self {}
+
+    fn combine(&self, _other: &Self) -> Self {}
+}
+
+/// A way of summarizing items, so that G-trees can cache the summaries of their subtrees.
+pub trait Augmentation<Item>: Clone + Debug {
+    type Summary: Monoid;
+
+    fn summarize(item: &Item) -> Self::Summary;
+}
+
+/// The trivial augmentation, for trees that do not need any summaries.
+impl<Item> Augmentation<Item> for () {
+    type Summary = ();
+
+    fn summarize(_item: &Item) -> Self::Summary {}
+}
+
+/// The summary type of the augmentation of a NonemptySet.
+pub type Summary<S> = <<S as NonemptySet>::Augmentation as Augmentation<<S as NonemptySet>::Item>>::Summary;
+
+/// Summarize a single item according to the augmentation of `S`.
+pub fn summarize<S: NonemptySet>(item: &S::Item) -> Summary<S> {
+    return <S::Augmentation as Augmentation<S::Item>>::summarize(item);
+}
+
+impl<S: NonemptySet> GTree<S> {
+    /// Return the combined summary of all items in the tree, in O(1) time.
+    pub fn summary(&self) -> Summary<S> {
+        match self {
+            GTree::Empty => return Summary::<S>::neutral(),
+            GTree::NonEmpty(node) => return node.summary.clone(),
+        }
+    }
+}
+
+impl<S: NonemptySet> GTree<S>
+where
+    S::Item: Ord,
+{
+    /// Return the combined summary of all items in the tree that lie within `range`, combined in ascending order.
+    pub fn fold_range<R: RangeBounds<S::Item>>(&self, range: R) -> Summary<S> {
+        return fold_range_(self, range.start_bound(), range.end_bound(), None, None);
+    }
+}
+
+// Whether all items strictly greater than `greater_than` and strictly less than `less_than` (None meaning no constraint) lie within the range.
+fn covers<I: Ord>(lower: Bound<&I>, upper: Bound<&I>, greater_than: Option<&I>, less_than: Option<&I>) -> bool {
+    let covers_lower = match (lower, greater_than) {
+        (Bound::Unbounded, _) => true,
+        (_, None) => false,
+        (Bound::Included(lower) | Bound::Excluded(lower), Some(greater_than)) => greater_than >= lower,
+    };
+    let covers_upper = match (upper, less_than) {
+        (Bound::Unbounded, _) => true,
+        (_, None) => false,
+        (Bound::Included(upper) | Bound::Excluded(upper), Some(less_than)) => less_than <= upper,
+    };
+    return covers_lower && covers_upper;
+}
+
+// Fold over the items of `t` that lie within the bounds, where all items of `t` are known to be strictly greater than `greater_than` and strictly less than `less_than` (None meaning no constraint).
+fn fold_range_<S: NonemptySet>(
+    t: &GTree<S>,
+    lower: Bound<&S::Item>,
+    upper: Bound<&S::Item>,
+    greater_than: Option<&S::Item>,
+    less_than: Option<&S::Item>,
+) -> Summary<S>
+where
+    S::Item: Ord,
+{
+    match t {
+        GTree::Empty => return Summary::<S>::neutral(),
+        GTree::NonEmpty(node) => {
+            if covers(lower, upper, greater_than, less_than) {
+                return node.summary.clone();
+            }
+
+            // We split the result into three parts: the items of the range in the left subtree that contains the lower bound, the pairs of the node whose items lie in the range, and the items of the range in the subtree (left or right) that contains the upper bound.
+            let lower_part;
+            let middle_part;
+            // The least item of the node that lies within the range, if the range is bounded below.
+            let least_in_range;
+
+            match lower {
+                Bound::Unbounded => {
+                    // The left subtrees of all items within the range lie completely within the range.
+                    lower_part = Summary::<S>::neutral();
+                    middle_part = node.set.fold_pairs(lower, upper);
+                    least_in_range = None;
+                }
+                Bound::Included(lower_item) | Bound::Excluded(lower_item) => match node.set.search(lower_item) {
+                    None => {
+                        // All items of the node are less than the range.
+                        return fold_range_(&node.right, lower, upper, greater_than, less_than);
+                    }
+                    Some((item, left_subtree)) => {
+                        if !below_upper(&item, upper) {
+                            // The range lies completely within the left subtree of `item`.
+                            return fold_range_(&left_subtree, lower, upper, greater_than, Some(&item));
+                        }
+
+                        if &item == lower_item {
+                            // The left subtree lies completely below the range.
+                            lower_part = Summary::<S>::neutral();
+                        } else {
+                            lower_part = fold_range_(&left_subtree, lower, upper, greater_than, Some(&item));
+                        }
+
+                        // The left subtrees of all greater items within the range lie completely within the range.
+                        let item_summary = if above_lower(&item, lower) {
+                            summarize::<S>(&item)
+                        } else {
+                            Summary::<S>::neutral()
+                        };
+                        middle_part = item_summary.combine(&node.set.fold_pairs(Bound::Excluded(&item), upper));
+                        least_in_range = Some(item);
+                    }
+                },
+            }
+
+            let greater_than_upper_part = least_in_range.as_ref().or(greater_than);
+            let upper_part = match upper {
+                Bound::Unbounded => {
+                    fold_range_(&node.right, lower, upper, greater_than_upper_part, less_than)
+                }
+                Bound::Included(upper_item) | Bound::Excluded(upper_item) => match node.set.search(upper_item) {
+                    None => fold_range_(&node.right, lower, upper, greater_than_upper_part, less_than),
+                    Some((item, left_subtree)) => {
+                        if &item == upper_item && matches!(upper, Bound::Included(_)) {
+                            // The item and its left subtree are already part of `middle_part`.
+                            Summary::<S>::neutral()
+                        } else {
+                            fold_range_(&left_subtree, lower, upper, greater_than_upper_part, Some(&item))
+                        }
+                    }
+                },
+            };
+
+            return lower_part.combine(&middle_part).combine(&upper_part);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+// This file implements building G-trees in bulk from sorted sequences of items and their ranks.
+//
+// Since the shape of a G-tree is determined by its items and their ranks alone, we can build it bottom-up in a single pass instead of inserting the items one by one. We keep the right spine of the tree built so far as a stack of unfinished G-nodes, whose ranks strictly decrease towards the top of the stack. The unfinished G-nodes above some G-node on the stack hold all items that are greater than its items so far.
+//
+// When an item of rank r arrives, all unfinished G-nodes of rank less than r are finished, each becoming the right subtree of the G-node below it on the stack, and the last one becoming the left subtree of the new item. The new item then either joins the G-node of rank r on top of the stack, or starts a new G-node. Every G-node is created exactly once, so building a tree of n items takes O(n) time, as long as the inner sets implement `NonemptySet::from_descending_pairs` in linear time.
+
+use crate::pointer::PointerFamily;
+use crate::{GTree, GTreeNode, NonemptySet};
+
+// A G-node whose item-left_subtree pairs (in ascending order) might still grow.
+struct Unfinished<S: NonemptySet> {
+    rank: u8,
+    pairs: Vec<(S::Item, GTree<S>)>,
+}
+
+impl<S: NonemptySet> Unfinished<S> {
+    fn finish(self, right: GTree<S>) -> GTree<S> {
+        let mut pairs = self.pairs;
+        pairs.reverse();
+        return GTree::NonEmpty(S::Pointers::new(GTreeNode::new(
+            S::from_descending_pairs(pairs),
+            right,
+            self.rank,
+        )));
+    }
+}
+
+impl<S: NonemptySet> GTree<S> {
+    /// Build the tree of the given items and their ranks in O(n) time. The items must be strictly ascending.
+    ///
+    /// The result is exactly the tree obtained by inserting all items at their ranks one by one, but much cheaper to compute.
+    pub fn from_sorted_ranked<I: IntoIterator<Item = (S::Item, u8)>>(items: I) -> Self
+    where
+        S::Item: Ord,
+    {
+        let mut spine: Vec<Unfinished<S>> = vec![];
+
+        for (item, rank) in items {
+            // The previous item is the greatest one of the G-node on top of the spine.
+            debug_assert!(
+                spine.last().is_none_or(|node| node.pairs.last().is_some_and(|(previous, _)| *previous < item)),
+                "The items must be strictly ascending."
+            );
+
+            // All unfinished G-nodes of lesser rank together form the left subtree of the new item.
+            let mut left_subtree = GTree::Empty;
+            while spine.last().is_some_and(|node| node.rank < rank) {
+                left_subtree = spine.pop().unwrap().finish(left_subtree);
+            }
+
+            match spine.last_mut() {
+                Some(node) if node.rank == rank => node.pairs.push((item, left_subtree)),
+                _ => spine.push(Unfinished {
+                    rank,
+                    pairs: vec![(item, left_subtree)],
+                }),
+            }
+        }
+
+        let mut t = GTree::Empty;
+        while let Some(node) = spine.pop() {
+            t = node.finish(t);
+        }
+        return t;
+    }
+}
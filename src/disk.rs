@@ -0,0 +1,665 @@
+// This file implements G-trees that live in a file rather than in memory.
+//
+// Every G-node is written to a page of `PAGE_SIZE` bytes. Its pairs are stored in descending order and split into vertices of K pairs, with the same boundaries as the vertices of the k-lists of `klist.rs`: the first vertex shares the page of the G-node, and every further vertex occupies a page of its own. Unlike the vertices of k-lists, these pages belong to a single G-node, and they are only ever read in order, either by a search or to load the whole G-node (see below). G-nodes and vertices refer to each other by page id, that is, by their offset in the file divided by `PAGE_SIZE`. All reads go through a page cache that holds a bounded number of decoded pages, so `has`, `insert` and `delete` only need the pages along a search path, and a tree can be larger than the available memory.
+//
+// Pages are never modified once written. Like the in-memory `insert` and `delete`, their counterparts here rebuild the G-nodes along the search path, and they append the new pages at the end of the file (copy-on-write), so every tree remains intact after updates. The first two pages of the file hold commit records, each of which names a root and the number of pages in use. `commit` syncs the new pages to disk before it overwrites the older of the two records, and `open` picks the newest valid record. A crash at any point thus leaves the last committed tree; pages that were written after the last commit are overwritten by later updates. Pages of trees that are no longer needed are never reclaimed, so the file only ever grows.
+//
+// Unzipping and zipping follow the in-memory `unzip` and `zip2` of `lib.rs`, but they operate on G-nodes that are loaded in their entirety (all vertices as a single `Vec` of pairs) rather than on `NonemptySet`s. The G-nodes that an update creates stay in memory until the update is complete, and are then written children first, so that intermediate G-nodes never reach the file. G-nodes that an update does not modify keep their pages. The `disk` fuzz target checks that a file-backed tree has the same shape as the in-memory tree after the same updates, and that both share G-nodes between versions in the same places (except that `delete` writes no pages at all if the item is missing).
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+/// The size of a page in bytes.
+pub const PAGE_SIZE: usize = 4096;
+
+type PageId = u64;
+
+// Pages 0 and 1 hold the commit records, so page id 0 can denote the empty tree.
+const EMPTY: PageId = 0;
+const FIRST_DATA_PAGE: PageId = 2;
+
+const MAGIC: &[u8; 8] = b"GTREEDSK";
+const NODE_TAG: u8 = 1;
+const VERTEX_TAG: u8 = 2;
+
+/// Items that can be written to pages.
+pub trait PageItem: Clone + Ord + Debug {
+    /// Append the encoding of the item to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+    /// Decode an item from the start of `bytes` and advance `bytes` past it. Return None if `bytes` does not start with a valid encoding.
+    fn decode(bytes: &mut &[u8]) -> Option<Self>;
+}
+
+macro_rules! impl_page_item_int {
+    ($($t:ty),*) => {
+        $(
+            impl PageItem for $t {
+                fn encode(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode(bytes: &mut &[u8]) -> Option<Self> {
+                    let (head, rest) = bytes.split_first_chunk()?;
+                    *bytes = rest;
+                    return Some(<$t>::from_le_bytes(*head));
+                }
+            }
+        )*
+    };
+}
+
+impl_page_item_int!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl PageItem for Vec<u8> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.len() as u32).encode(out);
+        out.extend_from_slice(self);
+    }
+
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let len = u32::decode(bytes)? as usize;
+        if bytes.len() < len {
+            return None;
+        }
+        let (item, rest) = bytes.split_at(len);
+        *bytes = rest;
+        return Some(item.to_vec());
+    }
+}
+
+/// A G-tree in a `DiskStore`, identified by the page of its root G-node.
+///
+/// Trees are persistent: updates return new trees and leave the old ones intact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DiskTree(PageId);
+
+impl DiskTree {
+    pub fn empty() -> Self {
+        return DiskTree(EMPTY);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.0 == EMPTY;
+    }
+}
+
+/// A G-node of a `DiskTree`, as returned by `DiskStore::node`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskNode<I> {
+    pub rank: u8,
+    /// The items and their left subtrees in descending order, one `Vec` per vertex. The first vertex shares the page of the G-node, every further vertex occupies a page of its own.
+    pub vertices: Vec<Vec<(I, DiskTree)>>,
+    pub right: DiskTree,
+}
+
+// A decoded page: a G-node with its first vertex, or a further vertex.
+#[derive(Debug)]
+struct Page<I> {
+    // The rank and the right subtree of a G-node, None for further vertices.
+    node: Option<(u8, PageId)>,
+    // The items and their left subtrees, in descending order.
+    pairs: Vec<(I, PageId)>,
+    next: PageId,
+}
+
+// A subtree during an update: either stored in the file, or a G-node that the update has created but not written yet.
+enum Subtree<I> {
+    Empty,
+    Stored(PageId),
+    Fresh(Box<Node<I>>),
+}
+
+impl<I> Subtree<I> {
+    fn stored(id: PageId) -> Self {
+        if id == EMPTY {
+            return Subtree::Empty;
+        } else {
+            return Subtree::Stored(id);
+        }
+    }
+}
+
+// The rank, the right subtree and all pairs (in descending order) of a stored G-node.
+type LoadedNode<I> = (u8, PageId, Vec<(I, PageId)>);
+
+// A G-node with all its pairs, in descending order.
+struct Node<I> {
+    rank: u8,
+    pairs: Vec<(I, Subtree<I>)>,
+    right: Subtree<I>,
+}
+
+// The contents of a commit record.
+#[derive(Debug, Clone, Copy)]
+struct Commit {
+    sequence: u64,
+    root: PageId,
+    // The number of pages in use, including the two pages of commit records.
+    page_count: u64,
+}
+
+// The file together with the page cache, which `has` updates through a shared reference.
+struct Pager<I> {
+    file: File,
+    capacity: usize,
+    // The cached pages, with the time of their last use.
+    cache: HashMap<PageId, (u64, Rc<Page<I>>)>,
+    // The cached pages by the time of their last use, to find the least recently used page.
+    recency: BTreeMap<u64, PageId>,
+    clock: u64,
+    reads: u64,
+}
+
+impl<I: PageItem> Pager<I> {
+    fn read_bytes(&mut self, id: PageId, len: usize) -> io::Result<Vec<u8>> {
+        let mut buffer = vec![0; len];
+        self.file.seek(SeekFrom::Start(id * PAGE_SIZE as u64))?;
+        self.file.read_exact(&mut buffer)?;
+        return Ok(buffer);
+    }
+
+    fn write_bytes(&mut self, id: PageId, bytes: &[u8]) -> io::Result<()> {
+        self.file.seek(SeekFrom::Start(id * PAGE_SIZE as u64))?;
+        return self.file.write_all(bytes);
+    }
+
+    // Return a page from the cache, or read it from the file and add it to the cache.
+    fn read(&mut self, id: PageId) -> io::Result<Rc<Page<I>>> {
+        self.clock += 1;
+        if let Some((last_use, page)) = self.cache.get_mut(&id) {
+            self.recency.remove(last_use);
+            self.recency.insert(self.clock, id);
+            *last_use = self.clock;
+            return Ok(page.clone());
+        }
+
+        let bytes = self.read_bytes(id, PAGE_SIZE)?;
+        self.reads += 1;
+        let page = Rc::new(decode_page(&bytes).ok_or_else(|| corrupt(id))?);
+        self.cache_page(id, page.clone());
+        return Ok(page);
+    }
+
+    fn cache_page(&mut self, id: PageId, page: Rc<Page<I>>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.cache.len() >= self.capacity {
+            let (_, evicted) = self.recency.pop_first().unwrap(/* the cache is not empty */);
+            self.cache.remove(&evicted);
+        }
+        self.clock += 1;
+        self.cache.insert(id, (self.clock, page));
+        self.recency.insert(self.clock, id);
+    }
+}
+
+/// A file of G-trees whose inner sets are k-lists with K pairs per page.
+///
+/// The items of a page (G-node or vertex) must fit into `PAGE_SIZE` bytes. The ranks of the items are supplied by the caller, like for the in-memory `insert`.
+pub struct DiskStore<const K: usize, I: PageItem> {
+    pager: RefCell<Pager<I>>,
+    // The number of pages in use; new pages are appended after them.
+    page_count: u64,
+    // The newest commit record.
+    committed: Commit,
+}
+
+impl<const K: usize, I: PageItem> DiskStore<K, I> {
+    /// Open the file at `path`, or create it if it does not exist, with a cache of (at most) `cache_pages` pages.
+    pub fn open<P: AsRef<Path>>(path: P, cache_pages: usize) -> io::Result<Self> {
+        if K == 0 || K > u16::MAX as usize {
+            panic!("K must be between 1 and {}.", u16::MAX);
+        }
+
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+        let is_new = file.metadata()?.len() == 0;
+        let mut pager = Pager {
+            file,
+            capacity: cache_pages,
+            cache: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+            reads: 0,
+        };
+
+        let committed = if is_new {
+            let initial = Commit {
+                sequence: 0,
+                root: EMPTY,
+                page_count: FIRST_DATA_PAGE,
+            };
+            pager.write_bytes(0, &encode_commit::<K>(&initial))?;
+            pager.write_bytes(1, &encode_commit::<K>(&initial))?;
+            pager.file.sync_all()?;
+            initial
+        } else {
+            // A record that cannot be read (for example, because a crash cut the file short) is as good as a corrupt one.
+            let mut newest: Option<Commit> = None;
+            for slot in 0..2 {
+                let record = match pager.read_bytes(slot, COMMIT_LEN) {
+                    Ok(bytes) => decode_commit::<K>(&bytes)?,
+                    Err(_) => None,
+                };
+                if let Some(record) = record {
+                    if newest.is_none_or(|newest| record.sequence > newest.sequence) {
+                        newest = Some(record);
+                    }
+                }
+            }
+            newest.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no valid commit record"))?
+        };
+
+        return Ok(DiskStore {
+            pager: RefCell::new(pager),
+            page_count: committed.page_count,
+            committed,
+        });
+    }
+
+    /// Return the tree of the latest commit.
+    pub fn root(&self) -> DiskTree {
+        return DiskTree(self.committed.root);
+    }
+
+    /// Make `t` the tree that `root` returns, also after reopening the file. All pages written so far reach the disk before the commit record does.
+    pub fn commit(&mut self, t: DiskTree) -> io::Result<()> {
+        let record = Commit {
+            sequence: self.committed.sequence + 1,
+            root: t.0,
+            page_count: self.page_count,
+        };
+
+        let mut pager = self.pager.borrow_mut();
+        pager.file.sync_data()?;
+        // Overwrite the older of the two records.
+        pager.write_bytes(record.sequence % 2, &encode_commit::<K>(&record))?;
+        pager.file.sync_data()?;
+
+        self.committed = record;
+        return Ok(());
+    }
+
+    /// Return whether `t` contains `key`.
+    pub fn has(&self, t: DiskTree, key: &I) -> io::Result<bool> {
+        let mut pager = self.pager.borrow_mut();
+        let mut current = t.0;
+
+        while current != EMPTY {
+            let mut vertex = pager.read(current)?;
+            let (_, right) = vertex.node.ok_or_else(|| corrupt(current))?;
+
+            // Descend into the left subtree of the least item greater than the key, or into the right subtree if there is no such item.
+            let mut subtree = right;
+            loop {
+                match vertex.pairs.binary_search_by(|(item, _)| key.cmp(item)) {
+                    Ok(_) => return Ok(true),
+                    Err(i) => {
+                        // The first i pairs of the vertex are greater than the key.
+                        if i > 0 {
+                            subtree = vertex.pairs[i - 1].1;
+                        }
+                        if i < vertex.pairs.len() || vertex.next == EMPTY {
+                            break;
+                        }
+                        vertex = pager.read(vertex.next)?;
+                    }
+                }
+            }
+            current = subtree;
+        }
+
+        return Ok(false);
+    }
+
+    /// Insert an item with the given rank into `t`, writing the new G-nodes to the file (but not committing them).
+    pub fn insert(&mut self, t: DiskTree, item: I, rank: u8) -> io::Result<DiskTree> {
+        let (left, right) = self.unzip(Subtree::stored(t.0), &item)?;
+        let mid = Subtree::Fresh(Box::new(Node {
+            rank,
+            pairs: vec![(item, Subtree::Empty)],
+            right: Subtree::Empty,
+        }));
+        let zipped = self.zip2(left, mid)?;
+        let zipped = self.zip2(zipped, right)?;
+        return Ok(DiskTree(self.write(zipped)?));
+    }
+
+    /// Remove an item from `t`, writing the new G-nodes to the file (but not committing them).
+    pub fn delete(&mut self, t: DiskTree, item: &I) -> io::Result<DiskTree> {
+        if !self.has(t, item)? {
+            return Ok(t);
+        }
+
+        let (left, right) = self.unzip(Subtree::stored(t.0), item)?;
+        let zipped = self.zip2(left, right)?;
+        return Ok(DiskTree(self.write(zipped)?));
+    }
+
+    /// Return the items of `t` in ascending order.
+    pub fn items(&self, t: DiskTree) -> io::Result<Vec<I>> {
+        let mut ret = vec![];
+        self.collect_items(t.0, &mut ret)?;
+        return Ok(ret);
+    }
+
+    /// Return the root G-node of `t`, or None if `t` is empty, for inspecting the shape of the tree.
+    pub fn node(&self, t: DiskTree) -> io::Result<Option<DiskNode<I>>> {
+        if t.is_empty() {
+            return Ok(None);
+        }
+
+        let mut pager = self.pager.borrow_mut();
+        let mut vertex = pager.read(t.0)?;
+        let (rank, right) = vertex.node.ok_or_else(|| corrupt(t.0))?;
+
+        let mut vertices = vec![];
+        loop {
+            vertices.push(vertex.pairs.iter().map(|(item, left)| (item.clone(), DiskTree(*left))).collect());
+            if vertex.next == EMPTY {
+                return Ok(Some(DiskNode {
+                    rank,
+                    vertices,
+                    right: DiskTree(right),
+                }));
+            }
+            let next = vertex.next;
+            vertex = pager.read(next)?;
+            if vertex.node.is_some() {
+                return Err(corrupt(next));
+            }
+        }
+    }
+
+    /// Return the number of pages in use, including the pages of uncommitted trees.
+    pub fn page_count(&self) -> u64 {
+        return self.page_count;
+    }
+
+    /// Return how many pages have been read from the file rather than from the cache.
+    pub fn page_reads(&self) -> u64 {
+        return self.pager.borrow().reads;
+    }
+
+    // Internal helper function: append the in-order items of the tree rooted at `id` to `out`.
+    fn collect_items(&self, id: PageId, out: &mut Vec<I>) -> io::Result<()> {
+        if id == EMPTY {
+            return Ok(());
+        }
+
+        let (_, right, pairs) = self.load(id)?;
+        for (item, left) in pairs.into_iter().rev() {
+            self.collect_items(left, out)?;
+            out.push(item);
+        }
+        return self.collect_items(right, out);
+    }
+
+    // Internal helper function: the rank, the right subtree and the pairs (in descending order) of the G-node at `id`.
+    fn load(&self, id: PageId) -> io::Result<LoadedNode<I>> {
+        let mut pager = self.pager.borrow_mut();
+        let mut vertex = pager.read(id)?;
+        let (rank, right) = vertex.node.ok_or_else(|| corrupt(id))?;
+
+        let mut pairs = vec![];
+        loop {
+            pairs.extend(vertex.pairs.iter().cloned());
+            if vertex.next == EMPTY {
+                return Ok((rank, right, pairs));
+            }
+            let next = vertex.next;
+            vertex = pager.read(next)?;
+            if vertex.node.is_some() {
+                return Err(corrupt(next));
+            }
+        }
+    }
+
+    // Internal helper function: take apart a non-empty subtree, loading its root G-node if it is stored.
+    fn take(&self, t: Subtree<I>) -> io::Result<Node<I>> {
+        match t {
+            Subtree::Empty => unreachable!("Cannot take apart an empty tree."),
+            Subtree::Fresh(node) => return Ok(*node),
+            Subtree::Stored(id) => {
+                let (rank, right, pairs) = self.load(id)?;
+                return Ok(Node {
+                    rank,
+                    pairs: pairs.into_iter().map(|(item, left)| (item, Subtree::stored(left))).collect(),
+                    right: Subtree::stored(right),
+                });
+            }
+        }
+    }
+
+    // Internal helper function: the rank of the root of a subtree, or None if it is empty.
+    fn rank(&self, t: &Subtree<I>) -> io::Result<Option<u8>> {
+        match t {
+            Subtree::Empty => return Ok(None),
+            Subtree::Fresh(node) => return Ok(Some(node.rank)),
+            Subtree::Stored(id) => {
+                let page = self.pager.borrow_mut().read(*id)?;
+                let (rank, _) = page.node.ok_or_else(|| corrupt(*id))?;
+                return Ok(Some(rank));
+            }
+        }
+    }
+
+    // Internal helper function: split a tree into the items less than `key` and the items greater than `key`, like `unzip` does for in-memory trees.
+    fn unzip(&self, t: Subtree<I>, key: &I) -> io::Result<(Subtree<I>, Subtree<I>)> {
+        if let Subtree::Empty = t {
+            return Ok((Subtree::Empty, Subtree::Empty));
+        }
+
+        let mut node = self.take(t)?;
+        match node.pairs.binary_search_by(|(item, _)| key.cmp(item)) {
+            Ok(i) => {
+                // The first i pairs are greater than the key, the pairs after index i are less than the key.
+                let lesser = node.pairs.split_off(i + 1);
+                let (_, left_subtree_of_key) = node.pairs.pop().unwrap(/* binary search returned i */);
+                return Ok((lift(lesser, left_subtree_of_key, node.rank), lift(node.pairs, node.right, node.rank)));
+            }
+            Err(0) => {
+                // All items are less than the key, so we split the right subtree.
+                let (left, right) = self.unzip(node.right, key)?;
+                return Ok((lift(node.pairs, left, node.rank), right));
+            }
+            Err(i) => {
+                // We split the left subtree of the least item greater than the key.
+                let lesser = node.pairs.split_off(i);
+                let subtree = std::mem::replace(&mut node.pairs[i - 1].1, Subtree::Empty);
+                let (left, greater) = self.unzip(subtree, key)?;
+                node.pairs[i - 1].1 = greater;
+                return Ok((lift(lesser, left, node.rank), Subtree::Fresh(Box::new(node))));
+            }
+        }
+    }
+
+    // Internal helper function: join two trees, all of whose items in `left` are less than those in `right`, like `zip2` does for in-memory trees.
+    fn zip2(&self, left: Subtree<I>, right: Subtree<I>) -> io::Result<Subtree<I>> {
+        let (left_rank, right_rank) = match (self.rank(&left)?, self.rank(&right)?) {
+            (None, _) => return Ok(right),
+            (_, None) => return Ok(left),
+            (Some(left_rank), Some(right_rank)) => (left_rank, right_rank),
+        };
+
+        if left_rank < right_rank {
+            // Zip left into the leftmost subtree of right.
+            let mut r = self.take(right)?;
+            let (_, leftmost) = r.pairs.last_mut().unwrap(/* G-nodes are never empty */);
+            *leftmost = self.zip2(left, std::mem::replace(leftmost, Subtree::Empty))?;
+            return Ok(Subtree::Fresh(Box::new(r)));
+        } else if left_rank > right_rank {
+            // Zip right into the right subtree of left.
+            let mut l = self.take(left)?;
+            l.right = self.zip2(std::mem::replace(&mut l.right, Subtree::Empty), right)?;
+            return Ok(Subtree::Fresh(Box::new(l)));
+        } else {
+            // Equal ranks. Join the pairs, with the right subtree of left being zipped into the leftmost subtree of right.
+            let mut l = self.take(left)?;
+            let mut r = self.take(right)?;
+            let (_, leftmost) = r.pairs.last_mut().unwrap(/* G-nodes are never empty */);
+            *leftmost = self.zip2(std::mem::replace(&mut l.right, Subtree::Empty), std::mem::replace(leftmost, Subtree::Empty))?;
+            // The greater pairs of right come first, because we store them in descending order.
+            r.pairs.append(&mut l.pairs);
+            return Ok(Subtree::Fresh(Box::new(r)));
+        }
+    }
+
+    // Internal helper function: write the fresh G-nodes of a subtree, children first, and return the page of its root.
+    fn write(&mut self, t: Subtree<I>) -> io::Result<PageId> {
+        let node = match t {
+            Subtree::Empty => return Ok(EMPTY),
+            Subtree::Stored(id) => return Ok(id),
+            Subtree::Fresh(node) => *node,
+        };
+
+        let right = self.write(node.right)?;
+        let mut pairs = Vec::with_capacity(node.pairs.len());
+        for (item, left) in node.pairs {
+            pairs.push((item, self.write(left)?));
+        }
+
+        // Only the final vertex may have free slots. We write the vertices back to front, so that every vertex knows the page of its successor, and the first vertex goes into the page of the G-node.
+        let mut chunks: Vec<Vec<_>> = vec![];
+        let mut pairs = pairs.into_iter().peekable();
+        while pairs.peek().is_some() {
+            chunks.push(pairs.by_ref().take(K).collect());
+        }
+
+        let mut next = EMPTY;
+        while chunks.len() > 1 {
+            let pairs = chunks.pop().unwrap();
+            next = self.append(Page { node: None, pairs, next })?;
+        }
+        let pairs = chunks.pop().unwrap(/* G-nodes are never empty */);
+        return self.append(Page {
+            node: Some((node.rank, right)),
+            pairs,
+            next,
+        });
+    }
+
+    // Internal helper function: write a page after all pages in use.
+    fn append(&mut self, page: Page<I>) -> io::Result<PageId> {
+        let bytes = encode_page(&page).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "the items of a vertex do not fit into a page"))?;
+        let id = self.page_count;
+
+        let mut pager = self.pager.borrow_mut();
+        pager.write_bytes(id, &bytes)?;
+        pager.cache_page(id, Rc::new(page));
+
+        self.page_count += 1;
+        return Ok(id);
+    }
+}
+
+// Internal helper function: create a G-node from a possibly empty list of pairs; if it is empty, the G-node is simply the right subtree.
+fn lift<I>(pairs: Vec<(I, Subtree<I>)>, right: Subtree<I>, rank: u8) -> Subtree<I> {
+    if pairs.is_empty() {
+        return right;
+    } else {
+        return Subtree::Fresh(Box::new(Node { rank, pairs, right }));
+    }
+}
+
+fn corrupt(id: PageId) -> io::Error {
+    return io::Error::new(io::ErrorKind::InvalidData, format!("page {} is corrupt", id));
+}
+
+// Internal helper function: encode a page, or return None if it does not fit into `PAGE_SIZE` bytes.
+fn encode_page<I: PageItem>(page: &Page<I>) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(PAGE_SIZE);
+    match page.node {
+        Some((rank, right)) => {
+            out.push(NODE_TAG);
+            out.push(rank);
+            right.encode(&mut out);
+        }
+        None => out.push(VERTEX_TAG),
+    }
+    page.next.encode(&mut out);
+    (page.pairs.len() as u16).encode(&mut out);
+    for (item, left) in page.pairs.iter() {
+        item.encode(&mut out);
+        left.encode(&mut out);
+    }
+
+    if out.len() > PAGE_SIZE {
+        return None;
+    }
+    out.resize(PAGE_SIZE, 0);
+    return Some(out);
+}
+
+fn decode_page<I: PageItem>(mut bytes: &[u8]) -> Option<Page<I>> {
+    let node = match u8::decode(&mut bytes)? {
+        NODE_TAG => Some((u8::decode(&mut bytes)?, u64::decode(&mut bytes)?)),
+        VERTEX_TAG => None,
+        _ => return None,
+    };
+    let next = u64::decode(&mut bytes)?;
+    let count = u16::decode(&mut bytes)?;
+    if count == 0 {
+        return None;
+    }
+
+    let mut pairs = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        pairs.push((I::decode(&mut bytes)?, u64::decode(&mut bytes)?));
+    }
+    return Some(Page { node, pairs, next });
+}
+
+// Magic, page size, K, sequence number, root, page count, checksum.
+const COMMIT_LEN: usize = 8 + 4 + 4 + 8 + 8 + 8 + 8;
+
+fn encode_commit<const K: usize>(record: &Commit) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    (PAGE_SIZE as u32).encode(&mut out);
+    (K as u32).encode(&mut out);
+    record.sequence.encode(&mut out);
+    record.root.encode(&mut out);
+    record.page_count.encode(&mut out);
+    let checksum = fnv1a(&out);
+    checksum.encode(&mut out);
+    return out;
+}
+
+// Return None for a torn or otherwise corrupt record, and an error for a valid record of a file with a different page size or K.
+fn decode_commit<const K: usize>(record: &[u8]) -> io::Result<Option<Commit>> {
+    let (contents, mut checksum) = record.split_at(COMMIT_LEN - 8);
+    if &contents[..8] != MAGIC || u64::decode(&mut checksum) != Some(fnv1a(contents)) {
+        return Ok(None);
+    }
+
+    let mut bytes = &contents[8..];
+    let page_size = u32::decode(&mut bytes).unwrap(/* length checked above */);
+    let k = u32::decode(&mut bytes).unwrap(/* length checked above */);
+    if page_size as usize != PAGE_SIZE || k as usize != K {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("the file uses pages of {} bytes with K = {}", page_size, k),
+        ));
+    }
+
+    return Ok(Some(Commit {
+        sequence: u64::decode(&mut bytes).unwrap(/* length checked above */),
+        root: u64::decode(&mut bytes).unwrap(/* length checked above */),
+        page_count: u64::decode(&mut bytes).unwrap(/* length checked above */),
+    }));
+}
+
+// The 64 bit FNV-1a hash, which (unlike `DefaultHasher`) is guaranteed to stay the same across Rust versions.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    return hash;
+}
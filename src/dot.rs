@@ -0,0 +1,139 @@
+// This file renders G-trees in the DOT language of Graphviz, for debugging.
+//
+// Every G-node becomes a record that shows its rank and its items, with an edge from each item to its left subtree and an edge from the last field to the right subtree. G-nodes are identified by their addresses, so a G-node that is shared between several trees (for example, between two versions of a tree) is drawn only once, and structural sharing shows up as G-nodes with several incoming edges. Optionally, the internal vertices of the inner sets (see `NonemptySetMeta::vertex_chain`) are drawn as well, again sharing vertices that are shared in memory.
+
+use std::collections::HashSet;
+use std::fmt::{Debug, Write};
+
+use crate::{GTree, GTreeNode, NonemptySetMeta};
+
+/// Options for `to_dot_with`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DotOptions {
+    /// Also draw the chain of internal vertices of every inner set, such as the vertices of a k-list.
+    pub show_vertices: bool,
+}
+
+/// Render a G-tree as a Graphviz digraph.
+pub fn to_dot<S: NonemptySetMeta>(t: &GTree<S>) -> String
+where
+    S::Item: Debug,
+{
+    return to_dot_with(&[("tree", t)], DotOptions::default());
+}
+
+/// Render several named G-trees (typically several versions of the same tree) as a single Graphviz digraph, drawing every G-node only once, no matter how many of the trees share it.
+pub fn to_dot_with<S: NonemptySetMeta>(trees: &[(&str, &GTree<S>)], options: DotOptions) -> String
+where
+    S::Item: Debug,
+{
+    let mut drawing = Drawing {
+        options,
+        out: String::new(),
+        nodes: HashSet::new(),
+        vertices: HashSet::new(),
+    };
+
+    writeln!(drawing.out, "digraph gtree {{").unwrap();
+    writeln!(drawing.out, "    node [shape=record];").unwrap();
+
+    for (i, (name, t)) in trees.iter().enumerate() {
+        writeln!(drawing.out, "    tree{} [shape=plaintext, label=\"{}\"];", i, escape(name)).unwrap();
+        match t {
+            GTree::Empty => {
+                writeln!(drawing.out, "    tree{}_empty [shape=point];", i).unwrap();
+                writeln!(drawing.out, "    tree{} -> tree{}_empty;", i, i).unwrap();
+            }
+            GTree::NonEmpty(node) => {
+                writeln!(drawing.out, "    tree{} -> {};", i, node_id(node)).unwrap();
+                drawing.draw_node(node);
+            }
+        }
+    }
+
+    writeln!(drawing.out, "}}").unwrap();
+    return drawing.out;
+}
+
+fn node_id<S: NonemptySetMeta>(node: &GTreeNode<S>) -> String {
+    return format!("n{:p}", node as *const GTreeNode<S>);
+}
+
+// Escape a string for use in a record label.
+fn escape(s: &str) -> String {
+    let mut escaped = String::new();
+    for c in s.chars() {
+        if matches!(c, '{' | '}' | '|' | '<' | '>' | '"' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    return escaped;
+}
+
+// The state of a rendering: the output so far, and the addresses of all G-nodes and vertices that have already been drawn. The two are tracked separately, since the first vertex of an inner set may live at the very address of its G-node.
+struct Drawing {
+    options: DotOptions,
+    out: String,
+    nodes: HashSet<*const ()>,
+    vertices: HashSet<*const ()>,
+}
+
+impl Drawing {
+    // Draw a G-node and all G-nodes below it, unless they have already been drawn.
+    fn draw_node<S: NonemptySetMeta>(&mut self, node: &GTreeNode<S>)
+    where
+        S::Item: Debug,
+    {
+        if !self.nodes.insert(node as *const GTreeNode<S> as *const ()) {
+            return;
+        }
+
+        let id = node_id(node);
+        let mut pairs: Vec<_> = node.set.pairs_descending_lent().collect();
+        pairs.reverse();
+
+        let fields: Vec<String> = pairs
+            .iter()
+            .enumerate()
+            .map(|(i, (item, _))| format!("<i{}> {}", i, escape(&format!("{:?}", &**item))))
+            .collect();
+        writeln!(
+            self.out,
+            "    {} [label=\"{{rank {}|{{{}|<right> }}}}\"];",
+            id,
+            node.rank,
+            fields.join("|")
+        )
+        .unwrap();
+
+        for (i, (_, left_subtree)) in pairs.iter().enumerate() {
+            if let GTree::NonEmpty(left) = left_subtree {
+                writeln!(self.out, "    {}:i{} -> {};", id, i, node_id(left)).unwrap();
+                self.draw_node(left);
+            }
+        }
+
+        if let GTree::NonEmpty(right) = &node.right {
+            writeln!(self.out, "    {}:right -> {};", id, node_id(right)).unwrap();
+            self.draw_node(right);
+        }
+
+        if self.options.show_vertices {
+            let mut previous = id;
+            for (address, items) in node.set.vertex_chain() {
+                let vertex_id = format!("v{:p}", address);
+                writeln!(self.out, "    {} -> {} [style=dashed];", previous, vertex_id).unwrap();
+
+                if !self.vertices.insert(address) {
+                    // The rest of the chain is shared as well, and has already been drawn.
+                    break;
+                }
+
+                let fields: Vec<String> = items.iter().map(|item| escape(&format!("{:?}", &**item))).collect();
+                writeln!(self.out, "    {} [style=dashed, label=\"{}\"];", vertex_id, fields.join("|")).unwrap();
+                previous = vertex_id;
+            }
+        }
+    }
+}
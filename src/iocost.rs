@@ -0,0 +1,142 @@
+// This file implements an I/O cost model for G-trees, to study their behaviour in external memory.
+//
+// The model treats every G-node (including the first vertex of its inner set) and every further k-list vertex as a block. Visiting a block counts as a block read, and allocating a block (or modifying it via `PointerFamily::make_mut`) counts as a block write. `has`, `get`, `unzip` and `zip2` (and thus `insert` and `delete`) report the G-nodes they visit, and k-lists report the vertices they visit; every shared allocation is a block, so the pointer families report the writes. Other operations are not instrumented.
+//
+// An optional buffer holds the M most recently used blocks (LRU). Reading a block in the buffer is free, written blocks enter the buffer. Blocks are identified by their address, so a block that is freed and reallocated elsewhere counts as a new block.
+//
+// Counting only happens within `IoModel::measure`, and only on the current thread. Outside of it, instrumented code merely checks a thread-local flag. The model is only compiled with the `iocost` feature; without it, the hooks in the instrumented code do nothing at all.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+
+/// The numbers of block transfers that an operation caused.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IoCounts {
+    pub reads: usize,
+    pub writes: usize,
+}
+
+impl IoCounts {
+    pub fn total(&self) -> usize {
+        return self.reads + self.writes;
+    }
+}
+
+/// A simulated external memory with a buffer of a fixed number of blocks.
+#[derive(Debug)]
+pub struct IoModel {
+    tracker: Tracker,
+}
+
+impl IoModel {
+    /// Create a model whose buffer holds `buffer_blocks` blocks (zero for no buffer).
+    pub fn new(buffer_blocks: usize) -> Self {
+        return IoModel {
+            tracker: Tracker::new(buffer_blocks),
+        };
+    }
+
+    /// Run `f`, and return its result together with the block transfers that it caused. The buffer keeps its contents between calls.
+    pub fn measure<R, F: FnOnce() -> R>(&mut self, f: F) -> (R, IoCounts) {
+        let tracker = std::mem::replace(&mut self.tracker, Tracker::new(0));
+        let before = tracker.counts;
+        ACTIVE.with(|active| {
+            if active.borrow().is_some() {
+                panic!("Cannot measure I/O within a measurement.");
+            }
+            *active.borrow_mut() = Some(tracker);
+        });
+
+        let ret = f();
+
+        self.tracker = ACTIVE.with(|active| active.borrow_mut().take()).unwrap(/* installed above */);
+        let counts = IoCounts {
+            reads: self.tracker.counts.reads - before.reads,
+            writes: self.tracker.counts.writes - before.writes,
+        };
+        return (ret, counts);
+    }
+}
+
+thread_local! {
+    static ACTIVE: RefCell<Option<Tracker>> = const { RefCell::new(None) };
+}
+
+/// Count a visit to a block, if a measurement is running.
+pub(crate) fn read<T>(block: &T) {
+    let address = block as *const T as usize;
+    ACTIVE.with(|active| {
+        if let Some(tracker) = active.borrow_mut().as_mut() {
+            if !tracker.buffer.access(address) {
+                tracker.counts.reads += 1;
+            }
+        }
+    });
+}
+
+/// Count a newly allocated or modified block, if a measurement is running.
+pub(crate) fn write<T>(block: &T) {
+    let address = block as *const T as usize;
+    ACTIVE.with(|active| {
+        if let Some(tracker) = active.borrow_mut().as_mut() {
+            tracker.buffer.access(address);
+            tracker.counts.writes += 1;
+        }
+    });
+}
+
+#[derive(Debug)]
+struct Tracker {
+    buffer: LruBuffer,
+    counts: IoCounts,
+}
+
+impl Tracker {
+    fn new(buffer_blocks: usize) -> Self {
+        return Tracker {
+            buffer: LruBuffer {
+                capacity: buffer_blocks,
+                blocks: HashMap::new(),
+                recency: BTreeMap::new(),
+                clock: 0,
+            },
+            counts: IoCounts::default(),
+        };
+    }
+}
+
+#[derive(Debug)]
+struct LruBuffer {
+    capacity: usize,
+    // The buffered blocks, with the time of their last use.
+    blocks: HashMap<usize, u64>,
+    // The buffered blocks by the time of their last use, to find the least recently used block.
+    recency: BTreeMap<u64, usize>,
+    clock: u64,
+}
+
+impl LruBuffer {
+    // Mark a block as used, adding it to the buffer if necessary. Return whether it was buffered already.
+    fn access(&mut self, address: usize) -> bool {
+        if self.capacity == 0 {
+            return false;
+        }
+
+        self.clock += 1;
+        match self.blocks.insert(address, self.clock) {
+            Some(last_use) => {
+                self.recency.remove(&last_use);
+                self.recency.insert(self.clock, address);
+                return true;
+            }
+            None => {
+                self.recency.insert(self.clock, address);
+                if self.blocks.len() > self.capacity {
+                    let (_, evicted) = self.recency.pop_first().unwrap(/* the buffer is not empty */);
+                    self.blocks.remove(&evicted);
+                }
+                return false;
+            }
+        }
+    }
+}
@@ -0,0 +1,242 @@
+// This file implements in-order iteration and range queries over G-trees.
+//
+// The iterators keep an explicit deque of the parts of the tree that have not been yielded yet, in ascending order: items that can be yielded directly, and subtrees that still need to be expanded. Iterating from the front expands subtrees at the front of the deque, iterating from the back expands them at the back. Every G-node is expanded exactly once, and the iterators never recurse.
+//
+// Range iterators additionally prune the frontier while expanding a G-node: they drop all items that lie outside the range, and all subtrees whose key interval does not intersect the range. Hence, they only descend into the subtrees that can contain items of the range.
+
+use std::collections::VecDeque;
+use std::ops::{Bound, RangeBounds};
+
+use crate::{above_lower, below_upper, GTree, NonemptySet};
+
+enum Frontier<'a, S: NonemptySet> {
+    Item(&'a S::Item),
+    Tree(&'a GTree<S>),
+}
+
+/// An iterator over the items of a G-tree in ascending order, created by `GTree::iter`.
+pub struct Iter<'a, S: NonemptySet> {
+    frontier: VecDeque<Frontier<'a, S>>,
+}
+
+impl<'a, S: NonemptySet> Iter<'a, S> {
+    fn new(t: &'a GTree<S>) -> Self {
+        let mut frontier = VecDeque::new();
+        frontier.push_back(Frontier::Tree(t));
+        return Iter { frontier };
+    }
+
+    // Replace a subtree at the front of the frontier with its contents.
+    fn expand_front(&mut self, t: &'a GTree<S>) {
+        if let GTree::NonEmpty(node) = t {
+            // Pushing to the front reverses the order, so we push the greatest parts first.
+            self.frontier.push_front(Frontier::Tree(&node.right));
+            for (item, left_subtree) in node.set.pairs_descending() {
+                self.frontier.push_front(Frontier::Item(item));
+                self.frontier.push_front(Frontier::Tree(left_subtree));
+            }
+        }
+    }
+
+    // Replace a subtree at the back of the frontier with its contents.
+    fn expand_back(&mut self, t: &'a GTree<S>) {
+        if let GTree::NonEmpty(node) = t {
+            let pairs: Vec<_> = node.set.pairs_descending().collect();
+            for (item, left_subtree) in pairs.into_iter().rev() {
+                self.frontier.push_back(Frontier::Tree(left_subtree));
+                self.frontier.push_back(Frontier::Item(item));
+            }
+            self.frontier.push_back(Frontier::Tree(&node.right));
+        }
+    }
+}
+
+impl<'a, S: NonemptySet> Iterator for Iter<'a, S> {
+    type Item = &'a S::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.frontier.pop_front()? {
+                Frontier::Item(item) => return Some(item),
+                Frontier::Tree(t) => self.expand_front(t),
+            }
+        }
+    }
+}
+
+impl<'a, S: NonemptySet> DoubleEndedIterator for Iter<'a, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.frontier.pop_back()? {
+                Frontier::Item(item) => return Some(item),
+                Frontier::Tree(t) => self.expand_back(t),
+            }
+        }
+    }
+}
+
+impl<S: NonemptySet> GTree<S> {
+    /// Iterate over all items in the tree in ascending order.
+    pub fn iter(&self) -> Iter<'_, S> {
+        return Iter::new(self);
+    }
+}
+
+impl<'a, S: NonemptySet> IntoIterator for &'a GTree<S> {
+    type Item = &'a S::Item;
+    type IntoIter = Iter<'a, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        return self.iter();
+    }
+}
+
+/// An iterator over the items of a G-tree that lie in a range, in ascending order. Created by `GTree::range`.
+pub struct Range<'a, S: NonemptySet> {
+    frontier: VecDeque<Frontier<'a, S>>,
+    lower: Bound<S::Item>,
+    upper: Bound<S::Item>,
+}
+
+impl<'a, S: NonemptySet> Range<'a, S>
+where
+    S::Item: Ord,
+{
+    fn new(t: &'a GTree<S>, lower: Bound<S::Item>, upper: Bound<S::Item>) -> Self {
+        let mut frontier = VecDeque::new();
+        frontier.push_back(Frontier::Tree(t));
+        return Range {
+            frontier,
+            lower,
+            upper,
+        };
+    }
+
+    fn above_lower(&self, item: &S::Item) -> bool {
+        return above_lower(item, self.lower.as_ref());
+    }
+
+    fn below_upper(&self, item: &S::Item) -> bool {
+        return below_upper(item, self.upper.as_ref());
+    }
+
+    // Whether a subtree whose items are all strictly greater than `greater_than` and strictly less than `less_than` (None meaning no constraint) might contain items in the range.
+    fn might_intersect(&self, greater_than: Option<&S::Item>, less_than: Option<&S::Item>) -> bool {
+        let fits_upper = match (greater_than, &self.upper) {
+            (None, _) | (_, Bound::Unbounded) => true,
+            (Some(greater_than), Bound::Included(upper)) | (Some(greater_than), Bound::Excluded(upper)) => {
+                greater_than < upper
+            }
+        };
+        let fits_lower = match (less_than, &self.lower) {
+            (None, _) | (_, Bound::Unbounded) => true,
+            (Some(less_than), Bound::Included(lower)) | (Some(less_than), Bound::Excluded(lower)) => {
+                less_than > lower
+            }
+        };
+        return fits_upper && fits_lower;
+    }
+
+    // The parts of a G-node that can contribute to the range, in ascending order.
+    fn relevant_parts(&self, t: &'a GTree<S>) -> Vec<Frontier<'a, S>> {
+        match t {
+            GTree::Empty => return vec![],
+            GTree::NonEmpty(node) => match &self.upper {
+                Bound::Unbounded => return self.relevant_parts_of(node.set.pairs_descending(), &node.right),
+                // The pairs above the least item that is not below the upper bound lie above the range, and so do their left subtrees.
+                Bound::Included(upper) | Bound::Excluded(upper) => {
+                    return self.relevant_parts_of(node.set.pairs_descending_from(upper), &node.right);
+                }
+            },
+        }
+    }
+
+    // Internal helper function: the relevant parts of a G-node, given its pairs in descending order, starting at the first pair that can contribute to the range.
+    fn relevant_parts_of<Pairs: Iterator<Item = (&'a S::Item, &'a GTree<S>)>>(&self, pairs: Pairs, right: &'a GTree<S>) -> Vec<Frontier<'a, S>> {
+        let mut parts = vec![];
+        let mut pairs = pairs.peekable();
+
+        // The right subtree lies above all items, so it cannot intersect the range if the first pair lies at or above the upper bound.
+        if let Some((greatest, _)) = pairs.peek() {
+            if self.might_intersect(Some(greatest), None) {
+                parts.push(Frontier::Tree(right));
+            }
+        }
+
+        while let Some((item, left_subtree)) = pairs.next() {
+            if self.above_lower(item) && self.below_upper(item) {
+                parts.push(Frontier::Item(item));
+            }
+            let lesser_item = pairs.peek().map(|(lesser_item, _)| *lesser_item);
+            if self.might_intersect(lesser_item, Some(item)) {
+                parts.push(Frontier::Tree(left_subtree));
+            }
+            // All further pairs (and their left subtrees) lie below the range.
+            if lesser_item.is_some_and(|lesser_item| !self.above_lower(lesser_item)) {
+                break;
+            }
+        }
+
+        // We collected the parts in descending order.
+        parts.reverse();
+        return parts;
+    }
+}
+
+impl<'a, S: NonemptySet> Iterator for Range<'a, S>
+where
+    S::Item: Ord,
+{
+    type Item = &'a S::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.frontier.pop_front()? {
+                Frontier::Item(item) => return Some(item),
+                Frontier::Tree(t) => {
+                    for part in self.relevant_parts(t).into_iter().rev() {
+                        self.frontier.push_front(part);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'a, S: NonemptySet> DoubleEndedIterator for Range<'a, S>
+where
+    S::Item: Ord,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.frontier.pop_back()? {
+                Frontier::Item(item) => return Some(item),
+                Frontier::Tree(t) => {
+                    for part in self.relevant_parts(t) {
+                        self.frontier.push_back(part);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<S: NonemptySet> GTree<S>
+where
+    S::Item: Clone + Ord,
+{
+    /// Iterate over all items in the tree that lie within `range`, in ascending order.
+    pub fn range<R: RangeBounds<S::Item>>(&self, range: R) -> Range<'_, S> {
+        return Range::new(self, range.start_bound().cloned(), range.end_bound().cloned());
+    }
+
+    /// Return the least item in the tree that lies within `range`.
+    pub fn first_in_range<R: RangeBounds<S::Item>>(&self, range: R) -> Option<&S::Item> {
+        return self.range(range).next();
+    }
+
+    /// Return the greatest item in the tree that lies within `range`.
+    pub fn last_in_range<R: RangeBounds<S::Item>>(&self, range: R) -> Option<&S::Item> {
+        return self.range(range).next_back();
+    }
+}
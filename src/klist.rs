@@ -1,18 +1,182 @@
-use std::{cmp::*, rc::Rc, fmt::Debug};
+use std::{borrow::Borrow, cmp::*, fmt::Debug, hash::{Hash, Hasher}, marker::PhantomData, ops::Bound, ptr::NonNull};
 
-use crate::{Set, GTree, NonemptySet, NonemptySetMeta};
+use crate::{Set, GTree, NonemptySet, Pair, NonemptySetMeta, above_lower, below_upper, iocost, augment::{summarize, Augmentation, Monoid}, map::KeyValue, memory::shared_allocation_bytes, merkle::MerkleHasher, pointer::{ArcFamily, PointerFamily, RcFamily}};
 
 /// A k-list, except for a few optimization details:
 ///
 /// - the list is known to be non-empty, so it always contains at least one vertex, and
 /// - the list stores its items in reverse order, to enable `insert_min` and `remove_min` in O(1) rather than O(n) time.
-#[derive(Debug, Clone)]
-pub struct NonemptyReverseKList<const K: usize, I: Clone + Ord + Debug> {
+///
+/// Every vertex caches the summary (according to the augmentation `A`) of its own items and their left subtrees, so that `fold_pairs` can skip over whole vertices.
+///
+/// Every vertex also caches the number of items in itself and all later vertices, so `len` takes constant time, and a jump pointer to a later vertex, so that `get_min` and `get_pair_by_index` reach any vertex in logarithmically many steps.
+///
+/// The methods that follow `next` pointers during searches and updates report the vertices they visit to the I/O cost model (see `iocost.rs`).
+///
+/// G-nodes of G-trees over k-lists are hashed with the hasher `H` (see `merkle.rs`), by default not at all.
+///
+/// Vertices and G-nodes are shared via the pointers of the family `P`. Use `ArcFamily` (see `NonemptyReverseArcKList`) for trees that can be shared between threads.
+#[derive(Clone)]
+pub struct NonemptyReverseKList<const K: usize, I: Clone + Ord + Debug, A: Augmentation<I> = (), P: PointerFamily = RcFamily, H: MerkleHasher<I> = ()> {
     data: [Option<(I, GTree<Self>)>; K],
-    next: Option<Rc<Self>>,
+    next: Option<P::Pointer<Self>>,
+    summary: A::Summary,
+    // The number of items in this vertex and all later vertices.
+    len: usize,
+    // A later vertex (see `jump_for`), or None for the final vertex.
+    jump: Option<Jump<Self>>,
+    augmentation: PhantomData<(A, H)>,
+}
+
+// Only the final vertex of a k-list may have free slots, so the number of vertices after a vertex follows from its `len`. We call it the depth of the vertex.
+//
+// The jump pointers follow the scheme of Myers ("An applicative random-access stack", 1983): the jump pointer of a vertex leads either to the next vertex, or, if the jumps of the next vertex and of its jump target skip equally many vertices, to the jump target of the jump target of the next vertex. Any vertex can then be reached with logarithmically many jumps and steps. Jump pointers are determined by the `next` pointers, so vertices are compared and hashed without them.
+
+// A jump pointer does not own its target: the `next` pointers keep every later vertex alive, and jump pointers that held reference counts of their own would make uniquely owned vertices look shared to `PointerFamily::make_mut`. Whenever a method changes the vertices after a vertex, it recomputes the jump pointer of that vertex (via `jump_for`) before returning, so no jump pointer that can still be followed outlives its target.
+struct Jump<T>(NonNull<T>);
+
+impl<T> Jump<T> {
+    fn to(target: &T) -> Self {
+        return Jump(NonNull::from(target));
+    }
+
+    fn get(&self) -> &T {
+        // SAFETY: the target is a later vertex of the list that holds this jump pointer, kept alive by the `next` pointers of that list (see above).
+        return unsafe { self.0.as_ref() };
+    }
 }
 
-impl<const K: usize, I: Clone + Ord + Debug> NonemptyReverseKList<K, I> {
+impl<T> Clone for Jump<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Jump<T> {}
+
+// A jump pointer is only used to read its target, like a shared reference.
+unsafe impl<T: Sync> Send for Jump<T> {}
+unsafe impl<T: Sync> Sync for Jump<T> {}
+
+impl<const K: usize, I: Clone + Ord + Debug, A: Augmentation<I>, P: PointerFamily, H: MerkleHasher<I>> NonemptyReverseKList<K, I, A, P, H> {
+    // Internal helper function: create a vertex, computing the summary of its data.
+    fn new(data: [Option<(I, GTree<Self>)>; K], next: Option<P::Pointer<Self>>) -> Self {
+        return NonemptyReverseKList {
+            summary: Self::summarize_data(&data),
+            len: data.iter().take_while(|slot| slot.is_some()).count() + next.as_ref().map_or(0, |next| next.len),
+            jump: Self::jump_for(&next),
+            data,
+            next,
+            augmentation: PhantomData,
+        };
+    }
+
+    // Internal helper function: a copy of this vertex, followed by `next` instead of its current successor.
+    fn with_next(&self, next: Option<P::Pointer<Self>>) -> Self {
+        let own_len = self.len - self.next.as_ref().map_or(0, |next| next.len);
+        return NonemptyReverseKList {
+            data: self.data.clone(),
+            summary: self.summary.clone(),
+            len: own_len + next.as_ref().map_or(0, |next| next.len),
+            jump: Self::jump_for(&next),
+            next,
+            augmentation: PhantomData,
+        };
+    }
+
+    // Internal helper function: the number of vertices after this one.
+    fn depth(&self) -> usize {
+        return (self.len - 1) / K;
+    }
+
+    // Internal helper function: the jump pointer for a vertex whose successor is `next`.
+    fn jump_for(next: &Option<P::Pointer<Self>>) -> Option<Jump<Self>> {
+        let next: &Self = next.as_ref()?;
+        if let Some(next_jump) = next.jump {
+            if let Some(next_jump_jump) = next_jump.get().jump {
+                if next.depth() - next_jump.get().depth() == next_jump.get().depth() - next_jump_jump.get().depth() {
+                    return Some(next_jump_jump);
+                }
+            }
+        }
+        return Some(Jump::to(next));
+    }
+
+    // Internal helper function: the later vertex (or `self`) with the given depth, which must not exceed the depth of `self`.
+    fn vertex_at_depth(&self, depth: usize) -> &Self {
+        let mut vertex = self;
+        while vertex.depth() > depth {
+            match vertex.jump {
+                Some(ref jump) if jump.get().depth() >= depth => vertex = jump.get(),
+                _ => vertex = vertex.next.as_deref().unwrap(/* only the final vertex has depth zero */),
+            }
+        }
+        return vertex;
+    }
+
+    // Internal helper function: compute the summary of the data of a vertex.
+    fn summarize_data(data: &[Option<(I, GTree<Self>)>; K]) -> A::Summary {
+        // The data is stored in descending order, but summaries are combined in ascending order.
+        let mut summary = A::Summary::neutral();
+        for (item, left_subtree) in data.iter().rev().flatten() {
+            summary = summary.combine(&left_subtree.summary()).combine(&A::summarize(item));
+        }
+        return summary;
+    }
+
+    // Internal helper function: the inverted index (0 denoting the *greatest* item) of the least item that is greater than or equal to `key`, if any.
+    fn locate(&self, key: &I) -> Option<usize> {
+        return self.locate_by(&|my_item: &I| key.cmp(my_item));
+    }
+
+    // Internal helper function: like `locate`, but with the comparison against the key supplied as a function (see `search_by`).
+    fn locate_by<F: Fn(&I) -> Ordering>(&self, cmp: &F) -> Option<usize> {
+        let mut vertex = self;
+        let mut offset = 0;
+
+        loop {
+            match vertex.data.binary_search_by(|opt| {
+                match opt {
+                    // Same comparison as in `search_by`.
+                    None => return Ordering::Greater,
+                    Some((my_item, _)) => return cmp(my_item),
+                }
+            }) {
+                Ok(i) => return Some(offset + i),
+                // All items of this vertex are less than the key, so the answer is the least item of the previous vertex (if any).
+                Err(0) => return offset.checked_sub(1),
+                Err(i) => {
+                    if i == K {
+                        // All items of this vertex are greater than the key.
+                        match vertex.next {
+                            None => return Some(offset + K - 1),
+                            Some(ref next) => {
+                                iocost::read(&**next);
+                                vertex = next;
+                                offset += K;
+                            }
+                        }
+                    } else {
+                        return Some(offset + i - 1);
+                    }
+                }
+            }
+        }
+    }
+
+    // Internal helper function: apply `f` to the left subtree of the item at the given inverted index (which must exist), cloning only those vertices that are shared.
+    fn update_left_subtree_by_inverted_index<F: FnOnce(&mut GTree<Self>)>(&mut self, index: usize, f: F) {
+        if index < K {
+            let (_, left_subtree) = self.data[index].as_mut().unwrap(/* index exists */);
+            f(left_subtree);
+            self.summary = Self::summarize_data(&self.data);
+        } else {
+            let next = P::make_mut(self.next.as_mut().unwrap(/* index exists */));
+            next.update_left_subtree_by_inverted_index(index - K, f);
+            self.jump = Self::jump_for(&self.next);
+        }
+    }
+
     // Internal helper function: remove the `n` greatest items from a list, with 1 <= n <= K.
     // Returns first the (up to n) items that were removed, then the valid remaining list (or None if it would be empty).
     fn remove_n_max(&self, n: usize) -> ([Option<(I, GTree<Self>)>; K], Option<Self>) {
@@ -24,7 +188,10 @@ impl<const K: usize, I: Clone + Ord + Debug> NonemptyReverseKList<K, I> {
                 self.data.clone(),
                 match self.next {
                     None => None,
-                    Some(ref next) => Some((**next).clone()),
+                    Some(ref next) => {
+                        iocost::read(&**next);
+                        Some((**next).clone())
+                    }
                 },
             );
         } else {
@@ -60,15 +227,13 @@ impl<const K: usize, I: Clone + Ord + Debug> NonemptyReverseKList<K, I> {
                         removed,
                         match new_data[0] {
                             None => None,
-                            Some(_) => Some(NonemptyReverseKList {
-                                data: new_data,
-                                next: None,
-                            }),
+                            Some(_) => Some(NonemptyReverseKList::new(new_data, None)),
                         },
                     );
                 }
                 Some(ref next) => {
                     // Recurse on the remaining vertices.
+                    iocost::read(&**next);
                     let (removed_rec, remaining_rec) = next.remove_n_max(n);
 
                     // println!("\nnew_data before: {:#?}\n", new_data);
@@ -86,10 +251,7 @@ impl<const K: usize, I: Clone + Ord + Debug> NonemptyReverseKList<K, I> {
                         removed,
                         match new_data[0] {
                             None => None,
-                            Some(_) => Some(NonemptyReverseKList {
-                                data: new_data,
-                                next: remaining_rec.map(Rc::new),
-                            }),
+                            Some(_) => Some(NonemptyReverseKList::new(new_data, remaining_rec.map(P::new))),
                         },
                     );
                 }
@@ -97,40 +259,197 @@ impl<const K: usize, I: Clone + Ord + Debug> NonemptyReverseKList<K, I> {
         }
     }
 
+    // The following helper functions modify a list in place. They obtain later vertices via `PointerFamily::make_mut`, so they only clone vertices that are shared, and they move items instead of cloning them. Like `update_left_subtree_by_inverted_index`, they recompute the jump pointer of a vertex after modifying its successors.
+
+    // Internal helper function: like `insert_min`, but in place.
+    fn insert_min_mut(&mut self, new_min: (I, GTree<Self>)) {
+        match self.next {
+            Some(ref next) => {
+                iocost::read(&**next);
+                let next = P::make_mut(self.next.as_mut().unwrap(/* matched above */));
+                next.insert_min_mut(new_min);
+            }
+            None => {
+                // `self` is the final vertex, so its items occupy exactly its first `len` slots.
+                if self.len < K {
+                    self.data[self.len] = Some(new_min);
+                    self.summary = Self::summarize_data(&self.data);
+                } else {
+                    self.next = Some(P::new(NonemptyReverseKList::singleton(new_min)));
+                }
+            }
+        }
+        self.len += 1;
+        self.jump = Self::jump_for(&self.next);
+    }
+
+    // Internal helper function: like `remove_n_max`, but in place. Return the removed items, and whether the list is empty now (in which case it must be discarded).
+    fn remove_n_max_mut(&mut self, n: usize) -> ([Option<Pair<Self>>; K], bool) {
+        if n == 0 || n > K {
+            unreachable!("Violated internal invariant!");
+        }
+
+        let removed: [_; K] = std::array::from_fn(|i| if i < n { self.data[i].take() } else { None });
+
+        if self.len <= n {
+            // This is the final vertex, and it has no more than n items, so nothing remains.
+            return (removed, true);
+        }
+
+        if n == K {
+            // The whole first vertex is removed, so its successor takes its place.
+            let next = self.next.take().unwrap(/* the list has more than K items */);
+            iocost::read(&*next);
+            *self = P::try_unwrap(next).unwrap_or_else(|next| (*next).clone());
+            return (removed, false);
+        }
+
+        // Move the remaining items of this vertex to the front, and refill the freed slots with the greatest items of the successor.
+        self.data.rotate_left(n);
+        if let Some(next) = self.next.as_mut() {
+            iocost::read(&**next);
+            let (mut removed_rec, next_is_empty) = P::make_mut(next).remove_n_max_mut(n);
+            for i in 0..n {
+                self.data[K - n + i] = removed_rec[i].take();
+            }
+            if next_is_empty {
+                self.next = None;
+            }
+        }
+        self.len -= n;
+        self.summary = Self::summarize_data(&self.data);
+        self.jump = Self::jump_for(&self.next);
+        return (removed, false);
+    }
+
+    // Internal helper function: build a list from the data of its vertices, in order. Only the last data may have free slots.
+    fn from_vertex_data(vertex_data: Vec<[Option<Pair<Self>>; K]>) -> Set<Self> {
+        let mut list = None;
+        for data in vertex_data.into_iter().rev() {
+            list = Some(NonemptyReverseKList::new(data, list.map(P::new)));
+        }
+        match list {
+            None => return Set::Empty,
+            Some(list) => return Set::NonEmpty(list),
+        }
+    }
+
+    // Internal helper function: iterate over all vertices of the list, starting with `self`.
+    fn vertices(&self) -> impl Iterator<Item = &Self> {
+        return std::iter::successors(Some(self), |vertex| vertex.next.as_deref());
+    }
+
     // Internal helper function: get an item by index, where index 0 denotes the *greatest* item.
-    fn get_pair_by_inverted_index(&self, index: usize) -> Option<&(I, GTree<Self>)>{
-        if index < K {
-            return self.data[index].as_ref();
+    fn get_pair_by_inverted_index(&self, index: usize) -> Option<&(I, GTree<Self>)> {
+        if index >= self.len {
+            return None;
         } else {
-            match self.next {
-                Some(ref next) => return next.get_pair_by_inverted_index(index - K),
-                None => return None,
+            // All vertices before the final one are full, so the item is in vertex number `index / K`.
+            let vertex = self.vertex_at_depth(self.depth() - index / K);
+            return vertex.data[index % K].as_ref();
+        }
+    }
+
+    // Internal helper function: the implementation of `search`, but with the comparison against the key supplied as a function.
+    // `cmp` is called with a stored item and must return how the key compares to that item.
+    fn search_by<F: Fn(&I) -> Ordering>(&self, cmp: &F) -> Option<(I, GTree<Self>)> {
+        return self.search_ref_by(cmp).cloned();
+    }
+
+    // Internal helper function: like `search_by`, but return a reference to the pair.
+    pub(crate) fn search_ref_by<F: Fn(&I) -> Ordering>(&self, cmp: &F) -> Option<&(I, GTree<Self>)> {
+        match self.data.binary_search_by(|opt| {
+            match opt {
+                // The `None`s are toward the end of the array, so `None`
+                // must compare as greater than any item.
+                None => return Ordering::Greater,
+                // We compare the key with the stored item instead of the
+                // other way round, to account for the reverse order.
+                Some((my_item, _)) => return cmp(my_item),
+            }
+        }) {
+            Ok(i) => {
+                return self.data[i].as_ref();
+            }
+            Err(i) => {
+                if i == 0 {
+                    return None;
+                } else if i == K {
+                    match self.next {
+                        None => return self.data[i - 1].as_ref(),
+                        Some(ref next) => {
+                            iocost::read(&**next);
+                            match next.search_ref_by(cmp) {
+                                None => return self.data[K - 1].as_ref(),
+                                Some(yay) => return Some(yay),
+                            }
+                        }
+                    }
+                } else {
+                    return self.data[i - 1].as_ref();
+                }
             }
         }
     }
 }
 
-impl<const K: usize, I: Clone + Ord + Debug> NonemptySet for NonemptyReverseKList<K, I> {
+// The cached summaries, lengths and jump pointers are determined by the data and the `next` pointers, so we do not print them.
+impl<const K: usize, I: Clone + Ord + Debug, A: Augmentation<I>, P: PointerFamily, H: MerkleHasher<I>> Debug for NonemptyReverseKList<K, I, A, P, H> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return f
+            .debug_struct("NonemptyReverseKList")
+            .field("data", &self.data)
+            .field("next", &self.next.as_deref())
+            .finish();
+    }
+}
+
+// Two k-lists with the same items always have the same vertex boundaries, so we can compare them vertex by vertex. Vertices that are shared between both lists are recognized by pointer equality. The cached summaries, lengths and jump pointers are determined by the data and the `next` pointers, so they are ignored.
+impl<const K: usize, I: Clone + Ord + Debug, A: Augmentation<I>, P: PointerFamily, H: MerkleHasher<I>> PartialEq for NonemptyReverseKList<K, I, A, P, H> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.data != other.data {
+            return false;
+        }
+
+        match (&self.next, &other.next) {
+            (None, None) => return true,
+            (Some(a), Some(b)) => return P::ptr_eq(a, b) || **a == **b,
+            _ => return false,
+        }
+    }
+}
+
+impl<const K: usize, I: Clone + Ord + Debug, A: Augmentation<I>, P: PointerFamily, H: MerkleHasher<I>> Eq for NonemptyReverseKList<K, I, A, P, H> {}
+
+impl<const K: usize, I: Clone + Ord + Debug + Hash, A: Augmentation<I>, P: PointerFamily, M: MerkleHasher<I>> Hash for NonemptyReverseKList<K, I, A, P, M> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+        self.next.as_deref().hash(state);
+    }
+}
+
+impl<const K: usize, I: Clone + Ord + Debug, A: Augmentation<I>, P: PointerFamily, H: MerkleHasher<I>> NonemptySet for NonemptyReverseKList<K, I, A, P, H> {
     type Item = I;
+    type Pointers = P;
+    type Augmentation = A;
+    type Hasher = H;
+
+    const TARGET_NODE_SIZE: usize = K;
 
     fn singleton(item: (Self::Item, GTree<Self>)) -> Self {
         let mut data = std::array::from_fn(|_| None);
         data[0] = Some(item);
 
-        return NonemptyReverseKList {
-            data,
-            next: None,
-        }
+        return NonemptyReverseKList::new(data, None);
     }
 
     fn insert_min(&self, new_min: (Self::Item, GTree<Self>)) -> Self {
         match self.next {
             // If self is not the final vertex, recurse.
             Some(ref next) => {
+                iocost::read(&**next);
                 let new_next = next.insert_min(new_min);
-                let mut new_self = self.clone();
-                new_self.next = Some(Rc::new(new_next));
-                return new_self;
+                return self.with_next(Some(P::new(new_next)));
             }
             None => {
                 // self is the final vertex, try to insert in the first free slot.
@@ -140,19 +459,13 @@ impl<const K: usize, I: Clone + Ord + Debug> NonemptySet for NonemptyReverseKLis
                         let mut new_data = self.data.clone();
                         new_data[i] = Some(new_min);
 
-                        return NonemptyReverseKList {
-                            data: new_data,
-                            next: None,
-                        }
+                        return NonemptyReverseKList::new(new_data, None)
                     }
                 }
 
                 // Found no free slot, append a new vertex.
-                let new_vertex = Rc::new(NonemptyReverseKList::singleton(new_min));
-                return NonemptyReverseKList {
-                    data: self.data.clone(),
-                    next: Some(new_vertex),
-                }
+                let new_vertex = P::new(NonemptyReverseKList::singleton(new_min));
+                return NonemptyReverseKList::new(self.data.clone(), Some(new_vertex))
             }
         }
     }
@@ -161,12 +474,12 @@ impl<const K: usize, I: Clone + Ord + Debug> NonemptySet for NonemptyReverseKLis
         match self.next {
             // If self is not the final vertex, recurse.
             Some(ref next) => {
+                iocost::read(&**next);
                 let (min, new_next) = next.remove_min();
-                let mut new_self = self.clone();
-                new_self.next = match new_next {
+                let new_self = self.with_next(match new_next {
                     Set::Empty => None,
-                    Set::NonEmpty(new_next) => Some(Rc::new(new_next)),
-                };
+                    Set::NonEmpty(new_next) => Some(P::new(new_next)),
+                });
                 return (min, Set::NonEmpty(new_self));
             }
             None => {
@@ -182,10 +495,7 @@ impl<const K: usize, I: Clone + Ord + Debug> NonemptySet for NonemptyReverseKLis
                             let mut new_data = self.data.clone();
                             new_data[i] = None;
 
-                            return (min.clone(), Set::NonEmpty(NonemptyReverseKList {
-                                data: new_data,
-                                next: None,
-                            }))
+                            return (min.clone(), Set::NonEmpty(NonemptyReverseKList::new(new_data, None)))
                         }
                     }
                 }
@@ -196,6 +506,44 @@ impl<const K: usize, I: Clone + Ord + Debug> NonemptySet for NonemptyReverseKLis
         }
     }
 
+    fn split_owned(mut self, key: &Self::Item) -> (Set<Self>, Option<(Self::Item, GTree<Self>)>, Set<Self>) {
+        // The items greater than the key form a prefix of the list. We remove them vertex by vertex, so that only the final vertex of the greater part has free slots.
+        let (greater, found) = match self.locate(key) {
+            None => (0, false),
+            Some(index) => {
+                let found = self.get_pair_by_inverted_index(index).is_some_and(|(item, _)| item == key);
+                (if found { index } else { index + 1 }, found)
+            }
+        };
+
+        let mut right_data = vec![];
+        let mut is_empty = false;
+        let mut remaining = greater;
+        while remaining > 0 {
+            let n = remaining.min(K);
+            let (removed, now_empty) = self.remove_n_max_mut(n);
+            right_data.push(removed);
+            is_empty = now_empty;
+            remaining -= n;
+        }
+        let right = Self::from_vertex_data(right_data);
+
+        let found = if found {
+            let (mut removed, now_empty) = self.remove_n_max_mut(1);
+            is_empty = now_empty;
+            removed[0].take()
+        } else {
+            None
+        };
+
+        return (if is_empty { Set::Empty } else { Set::NonEmpty(self) }, found, right);
+    }
+
+    fn insert_min_owned(mut self, new_min: (Self::Item, GTree<Self>)) -> Self {
+        self.insert_min_mut(new_min);
+        return self;
+    }
+
     fn split(&self, key: &Self::Item) -> (Set<Self>, Option<GTree<Self>> /* left subtree of key (if key is in self, else None) */, Set<Self>) {
         // Try to find key in self.
         // Because we store items in reverse, we use a comparison function that compares in reverse as well.
@@ -226,10 +574,8 @@ impl<const K: usize, I: Clone + Ord + Debug> NonemptySet for NonemptyReverseKLis
                             return None;
                         }
                     });
-                    Set::NonEmpty(NonemptyReverseKList {
-                        data: right_data, // safe to do this, i > 0, so right_data is not empty
-                        next: None,
-                    })
+                    // safe to do this, i > 0, so right_data is not empty
+                    Set::NonEmpty(NonemptyReverseKList::new(right_data, None))
                 };
                 // println!("right {:#?}", right);
 
@@ -264,13 +610,13 @@ impl<const K: usize, I: Clone + Ord + Debug> NonemptySet for NonemptyReverseKLis
                         }
                         Some(ref next) => {
                             // Recurse and append the right recursive return to ourselves.
+                            iocost::read(&**next);
                             let (left_rec, mid_rec, right_rec) = next.split(key);
                             // println!("recursive:\n{:#?}\n{:?}\n{:#?}", left_rec, mid_rec, right_rec);
 
                             match right_rec {
                                 Set::Empty => {
-                                    let mut cloned = self.clone();
-                                    cloned.next = None;
+                                    let cloned = self.with_next(None);
                                     return (
                                         left_rec,
                                         mid_rec,
@@ -278,8 +624,7 @@ impl<const K: usize, I: Clone + Ord + Debug> NonemptySet for NonemptyReverseKLis
                                     );
                                 }
                                 Set::NonEmpty(right_rec) => {
-                                    let mut cloned = self.clone();
-                                    cloned.next = Some(Rc::new(right_rec));
+                                    let cloned = self.with_next(Some(P::new(right_rec)));
                                     return (
                                         left_rec,
                                         mid_rec,
@@ -302,10 +647,7 @@ impl<const K: usize, I: Clone + Ord + Debug> NonemptySet for NonemptyReverseKLis
                         }
                     });
                     // println!("c {:?}", right_data);
-                    let right = Set::NonEmpty(NonemptyReverseKList {
-                        data: right_data,
-                        next: None,
-                    });
+                    let right = Set::NonEmpty(NonemptyReverseKList::new(right_data, None));
 
                     // println!("b {:?}", self);
                     // We obtain the left return by removing our first `i` items.
@@ -331,10 +673,8 @@ impl<const K: usize, I: Clone + Ord + Debug> NonemptySet for NonemptyReverseKLis
         match right.next {
             Some(ref right_next) => {
                 // Recurse and use the return value as the next vertex for the first vertex of `right`.
-                return NonemptyReverseKList {
-                    data: right.data.clone(),
-                    next: Some(Rc::new(Self::join(left, right_next))),
-                };
+                iocost::read(&**right_next);
+                return NonemptyReverseKList::new(right.data.clone(), Some(P::new(Self::join(left, right_next))));
             }
             None => {
                 // Actually need to do some work now.
@@ -352,10 +692,7 @@ impl<const K: usize, I: Clone + Ord + Debug> NonemptySet for NonemptyReverseKLis
 
                 if right_count == K {
                     // Right is full, so we can simply set right.next to left.
-                    return NonemptyReverseKList {
-                        data: right.data.clone(),
-                        next: Some(Rc::new(left.clone())),
-                    };
+                    return NonemptyReverseKList::new(right.data.clone(), Some(P::new(left.clone())));
                 } else {
                     // Right has K - right_count free slots, so move that many items from left into right, and then concatenate.
                     let to_move = K - (right_count);
@@ -372,51 +709,107 @@ impl<const K: usize, I: Clone + Ord + Debug> NonemptySet for NonemptyReverseKLis
                     // println!("new_data {:?}", new_data);
                     // println!("left_remaining {:?}", left_remaining);
                     
-                    return NonemptyReverseKList {
-                        data: new_data,
-                        next: left_remaining.map(|l| Rc::new(l)),
-                    };
+                    return NonemptyReverseKList::new(new_data, left_remaining.map(|l| P::new(l)));
                 }
             }
         }
     }
     
     fn search(&self, key: &Self::Item) -> Option<(Self::Item, GTree<Self>)> {
-        match self.data.binary_search_by(|opt| {
-            match opt {
-                // The `None`s are toward the end of the array, so `None`
-                // must compare as greater than any item.
-                None => return Ordering::Greater,
-                // We compare the key with the stored item instead of the
-                // other way round, to account for the reverse order.
-                Some((my_item, _)) => return key.cmp(my_item),
-            }
-        }) {
-            Ok(i) => {
-                return self.data[i].clone();
+        return self.search_by(&|my_item| key.cmp(my_item));
+    }
+
+    fn search_ref<Q: Ord + ?Sized>(&self, key: &Q) -> Option<(&Self::Item, &GTree<Self>)>
+    where
+        Self::Item: Borrow<Q>,
+    {
+        return self.search_ref_by(&|my_item: &I| key.cmp(my_item.borrow())).map(|(item, subtree)| (item, subtree));
+    }
+
+    fn pairs_descending(&self) -> impl Iterator<Item = (&Self::Item, &GTree<Self>)> {
+        // Walk the vertices in order, and the occupied slots of each vertex in order.
+        return self.vertices().flat_map(|vertex| {
+            vertex.data.iter().map_while(|slot| slot.as_ref().map(|(item, subtree)| (item, subtree)))
+        });
+    }
+
+    fn pairs_descending_from<Q: Ord + ?Sized>(&self, key: &Q) -> impl Iterator<Item = (&Self::Item, &GTree<Self>)>
+    where
+        Self::Item: Borrow<Q>,
+    {
+        // Jump to the vertex that contains the starting pair, then skip the greater pairs of that vertex.
+        let start = self.locate_by(&|my_item: &I| key.cmp(my_item.borrow())).unwrap_or(0);
+        let vertex = self.vertex_at_depth(self.depth() - start / K);
+        return vertex.pairs_descending().skip(start % K);
+    }
+
+    fn update_left_subtree<F: FnOnce(&mut GTree<Self>)>(&mut self, key: &Self::Item, f: F) -> bool {
+        match self.locate(key) {
+            None => return false,
+            Some(index) => {
+                self.update_left_subtree_by_inverted_index(index, f);
+                return true;
             }
-            Err(i) => {
-                if i == 0 {
-                    return None;
-                } else if i == K {
-                    match self.next {
-                        None => return self.data[i - 1].clone(),
-                        Some(ref next) => {
-                            match next.search(key) {
-                                None => return self.data[K - 1].clone(),
-                                Some(yay) => return Some(yay),
-                            }
-                        }
+        }
+    }
+
+    fn from_descending_pairs(pairs: Vec<(Self::Item, GTree<Self>)>) -> Self {
+        // Only the final vertex may have free slots, so we fill the vertices in chunks of K pairs, building the list from its final vertex backwards.
+        let mut chunks: Vec<Vec<_>> = vec![];
+        let mut pairs = pairs.into_iter().peekable();
+        while pairs.peek().is_some() {
+            chunks.push(pairs.by_ref().take(K).collect());
+        }
+
+        let mut ret = None;
+        for chunk in chunks.into_iter().rev() {
+            let mut chunk = chunk.into_iter();
+            let data = std::array::from_fn(|_| chunk.next());
+            ret = Some(NonemptyReverseKList::new(data, ret.map(P::new)));
+        }
+
+        return ret.expect("Cannot create a set from zero pairs.");
+    }
+
+    fn summary(&self) -> A::Summary {
+        // The vertices store successively lesser items, so we prepend the summary of each vertex.
+        let mut ret = A::Summary::neutral();
+        for vertex in self.vertices() {
+            ret = vertex.summary.combine(&ret);
+        }
+        return ret;
+    }
+
+    fn fold_pairs(&self, lower: Bound<&I>, upper: Bound<&I>) -> A::Summary {
+        let mut ret = A::Summary::neutral();
+
+        for vertex in self.vertices() {
+            let max = &vertex.data[0].as_ref().unwrap(/* vertices are never empty */).0;
+            let min = &vertex.data.iter().flatten().last().unwrap(/* vertices are never empty */).0;
+
+            if !above_lower(max, lower) {
+                // This vertex and all later ones lie below the range.
+                break;
+            } else if !below_upper(min, upper) {
+                // This vertex lies above the range.
+                continue;
+            } else if above_lower(min, lower) && below_upper(max, upper) {
+                // This vertex lies completely within the range, so we can use its cached summary.
+                ret = vertex.summary.combine(&ret);
+            } else {
+                for (item, left_subtree) in vertex.data.iter().flatten() {
+                    if above_lower(item, lower) && below_upper(item, upper) {
+                        ret = left_subtree.summary().combine(&summarize::<Self>(item)).combine(&ret);
                     }
-                } else {
-                    return self.data[i - 1].clone();
                 }
             }
         }
+
+        return ret;
     }
 }
 
-impl<const K: usize, I: Clone + Ord + Debug> NonemptySetMeta for NonemptyReverseKList<K, I> {
+impl<const K: usize, I: Clone + Ord + Debug, A: Augmentation<I>, P: PointerFamily, H: MerkleHasher<I>> NonemptySetMeta for NonemptyReverseKList<K, I, A, P, H> {
     /// Return a reference to the maximal item in the set.
     fn get_max(&self) -> &Self::Item {
         match self.data[0] {
@@ -427,36 +820,11 @@ impl<const K: usize, I: Clone + Ord + Debug> NonemptySetMeta for NonemptyReverse
 
     /// Return a reference to the minimal item in the set.
     fn get_min(&self) -> &Self::Item {
-        match self.next {
-            Some(ref next) => {
-                return next.get_min();
-            }
-            None => {
-                for i in (0..K).rev() {
-                    if let Some((ref item, _)) = self.data[i] {
-                        return &item;
-                    }
-                }
-                unreachable!("self.data contains at least one item.");
-            }
-        }
+        return &self.get_pair_by_inverted_index(self.len - 1).unwrap(/* the list is never empty */).0;
     }
 
     fn len(&self) -> usize {
-        match self.next {
-            Some(ref next) => {
-                return K + next.len();
-            }
-            None => {
-                for i in (0..K).rev() {
-                    if let Some(_) = self.data[i] {
-                        return i + 1;
-                    }
-                }
-                println!("{:?}", self);
-                unreachable!("self.data contains at least one item.");
-            }
-        }
+        return self.len;
     }
 
     fn item_slot_count(&self) -> usize {
@@ -470,8 +838,26 @@ impl<const K: usize, I: Clone + Ord + Debug> NonemptySetMeta for NonemptyReverse
         }
     }
 
-    fn get_pair_by_index(&self, index: usize) -> Option<&(Self::Item, GTree<Self>)> {
-        return self.get_pair_by_inverted_index(self.len() - (1 + index));
+    // The first vertex is stored inline in the G-node, all others are shared allocations.
+    fn heap_bytes(&self) -> usize {
+        return (self.vertices().count() - 1) * shared_allocation_bytes::<Self>();
+    }
+
+    fn get_pair_by_index(&self, index: usize) -> Option<(&Self::Item, &GTree<Self>)> {
+        if index >= self.len {
+            return None;
+        }
+        return self.get_pair_by_inverted_index(self.len - (1 + index)).map(|(item, subtree)| (item, subtree));
+    }
+
+    fn vertex_chain(&self) -> Vec<(*const (), Vec<&Self::Item>)> {
+        return self
+            .vertices()
+            .map(|vertex| {
+                let items = vertex.data.iter().flatten().map(|(item, _)| item).collect();
+                return (vertex as *const Self as *const (), items);
+            })
+            .collect();
     }
 
     fn from_descending(items: &[Self::Item]) -> Self {
@@ -489,8 +875,14 @@ impl<const K: usize, I: Clone + Ord + Debug> NonemptySetMeta for NonemptyReverse
     }
 }
 
-pub fn physical_height<const K: usize, T: Clone + Ord + Debug>(
-    t: &GTree<NonemptyReverseKList<K, T>>,
+/// A k-list whose G-trees can be shared between threads.
+pub type NonemptyReverseArcKList<const K: usize, I, A = ()> = NonemptyReverseKList<K, I, A, ArcFamily>;
+
+/// A k-list that can be used as the inner set of a `GTreeMap`.
+pub type NonemptyReverseKListMap<const K: usize, Key, Value> = NonemptyReverseKList<K, KeyValue<Key, Value>>;
+
+pub fn physical_height<const K: usize, T: Clone + Ord + Debug, A: Augmentation<T>, P: PointerFamily, H: MerkleHasher<T>>(
+    t: &GTree<NonemptyReverseKList<K, T, A, P, H>>,
 ) -> usize {
     // println!("t: {:#?}", t);
     match t {
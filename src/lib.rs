@@ -1,14 +1,55 @@
 // This file implements G-trees.
 #![feature(maybe_uninit_write_slice)]
 
+pub mod algebra;
+pub mod array;
+pub mod augment;
+pub mod bulk;
+pub mod disk;
+#[cfg(feature = "iocost")]
+pub mod iocost;
+pub mod dot;
 pub mod klist;
+pub mod iter;
+pub mod map;
+pub mod memory;
+pub mod merkle;
+pub mod mutable;
+pub mod nested;
+pub mod order;
+pub mod packed;
+pub mod pointer;
+pub mod prefix;
+pub mod rank;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod validate;
+
+// Without the `iocost` feature, the hooks of the I/O cost model (see `iocost.rs`) do nothing.
+#[cfg(not(feature = "iocost"))]
+mod iocost {
+    #[inline(always)]
+    pub(crate) fn read<T>(_block: &T) {}
+
+    #[inline(always)]
+    pub(crate) fn write<T>(_block: &T) {}
+}
 
+use std::borrow::Borrow;
 use std::collections::BTreeMap;
 use std::fmt::Debug;
-use std::{collections::BTreeSet, rc::Rc};
+use std::hash::{Hash, Hasher};
+use std::ops::{Bound, Deref};
+use std::collections::BTreeSet;
 
 use arbitrary::Arbitrary;
 
+use augment::{summarize, Augmentation, Monoid, Summary};
+use map::KeyValue;
+use merkle::{Digest, MerkleHasher};
+use pointer::{PointerFamily, RcFamily};
+use rank::{HashRank, RankDerivation};
+
 /*
 Definitions for NonemptySet and GTrees.
 */
@@ -25,6 +66,48 @@ impl<S: NonemptySet> Set<S> {
             Set::NonEmpty(s) => return s.insert_min(new_min),
         }
     }
+
+    fn insert_min_owned(self, new_min: (S::Item, GTree<S>)) -> S {
+        match self {
+            Set::Empty => return S::singleton(new_min),
+            Set::NonEmpty(s) => return s.insert_min_owned(new_min),
+        }
+    }
+}
+
+/// An item of a set of type `S`, together with its left subtree.
+pub type Pair<S> = (<S as NonemptySet>::Item, GTree<S>);
+
+/// An item of a set of type `S`, together with its rank.
+pub type RankedItem<S> = (<S as NonemptySet>::Item, u8);
+
+/// An item that a set lends out either by reference or, if the set does not store its items as they are (like packed and prefix sets), as a decoded temporary.
+#[derive(Debug)]
+pub enum Lent<'a, I> {
+    Borrowed(&'a I),
+    Owned(I),
+}
+
+impl<I> Deref for Lent<'_, I> {
+    type Target = I;
+
+    fn deref(&self) -> &I {
+        match self {
+            Lent::Borrowed(item) => return item,
+            Lent::Owned(item) => return item,
+        }
+    }
+}
+
+/// Split a set like `NonemptySet::split`, but return the item that is equal to `key` together with its left subtree, rather than only the subtree.
+fn split_with_item<S: NonemptySet>(s: &S, key: &S::Item) -> (Set<S>, Option<Pair<S>>, Set<S>) {
+    match s.split(key) {
+        (left_set, Some(left_subtree), right_set) => {
+            let (item, _) = s.search(key).unwrap(/* split found the key */);
+            return (left_set, Some((item, left_subtree)), right_set);
+        }
+        (left_set, None, right_set) => return (left_set, None, right_set),
+    }
 }
 
 /// Join an arbitrary lesser set with a non-empty greater set.
@@ -40,6 +123,15 @@ where
     Self: Sized,
 {
     type Item;
+    /// The pointers used for sharing G-nodes of G-trees of this set type, see `pointer.rs`.
+    type Pointers: PointerFamily;
+    /// How to summarize the items of G-trees of this set type, see `augment.rs`. Use `()` if no summaries are needed.
+    type Augmentation: Augmentation<Self::Item>;
+    /// How to hash the G-nodes of G-trees of this set type, see `merkle.rs`. Use `()` if no hashes are needed.
+    type Hasher: MerkleHasher<Self::Item>;
+
+    /// The number of items per G-node that the rank distribution should aim for. Used by `GTree::insert` to derive ranks.
+    const TARGET_NODE_SIZE: usize = 1;
 
     fn singleton(item: (Self::Item, GTree<Self>)) -> Self;
     fn split(
@@ -53,8 +145,135 @@ where
     fn join(left: &Self, right: &Self) -> Self;
     fn remove_min(&self) -> ((Self::Item, GTree<Self>), Set<Self>);
     fn insert_min(&self, new_min: (Self::Item, GTree<Self>)) -> Self;
+
+    // The following methods consume their sets, so that implementations can move pairs instead of cloning them. The update path uses them on G-nodes that it holds the only reference to. By default, they fall back to the borrowing methods.
+
+    /// Like `split`, but consume the set, and return the item that is equal to `key` together with its left subtree.
+    fn split_owned(self, key: &Self::Item) -> (Set<Self>, Option<Pair<Self>>, Set<Self>) {
+        return split_with_item(&self, key);
+    }
+    /// Like `join`, but consume both sets.
+    fn join_owned(left: Self, right: Self) -> Self {
+        return Self::join(&left, &right);
+    }
+    /// Like `remove_min`, but consume the set.
+    fn remove_min_owned(self) -> ((Self::Item, GTree<Self>), Set<Self>) {
+        return self.remove_min();
+    }
+    /// Like `insert_min`, but consume the set.
+    fn insert_min_owned(self, new_min: (Self::Item, GTree<Self>)) -> Self {
+        return self.insert_min(new_min);
+    }
+
     /// Return the item-left_subtree pair witht the least item that is greater than or equal to `key`. Return None if no such pair exists.
     fn search(&self, key: &Self::Item) -> Option<(Self::Item, GTree<Self>)>;
+    /// Like `search`, but return references into the set rather than clones, and accept any key that the items can be borrowed as (for example, a `&str` for `String` items).
+    fn search_ref<Q: Ord + ?Sized>(&self, key: &Q) -> Option<(&Self::Item, &GTree<Self>)>
+    where
+        Self::Item: Borrow<Q>,
+    {
+        // The pairs come in descending order, so the last pair whose item is not less than the key is the one we want.
+        return self.pairs_descending().take_while(|(item, _)| (*item).borrow() >= key).last();
+    }
+    /// Like `search_ref`, but return only the left subtree, together with whether the item is equal to `key`. Sets that cannot lend out references to their items cheaply (such as packed sets) override this.
+    fn search_left_subtree<Q: Ord + ?Sized>(&self, key: &Q) -> Option<(&GTree<Self>, bool)>
+    where
+        Self::Item: Borrow<Q>,
+    {
+        return self.search_ref(key).map(|(item, left_subtree)| (left_subtree, item.borrow() == key));
+    }
+    /// Iterate over all item-left_subtree pairs, from the greatest item to the least one.
+    fn pairs_descending(&self) -> impl Iterator<Item = (&Self::Item, &GTree<Self>)>;
+    /// Like `pairs_descending`, but the items may be lent out as decoded temporaries rather than by reference. Everything that does not need to hold on to the items beyond the iteration uses this, so that sets which cannot lend out references to their items cheaply (such as packed sets) need not keep decoded copies of them around.
+    fn pairs_descending_lent(&self) -> impl Iterator<Item = (Lent<'_, Self::Item>, &GTree<Self>)> {
+        return self.pairs_descending().map(|(item, left_subtree)| (Lent::Borrowed(item), left_subtree));
+    }
+    /// Like `pairs_descending`, but start at the pair that `search_ref(key)` returns (or at the greatest pair if it returns None). Sets with logarithmic search override this, so that range queries need not walk the pairs above the range.
+    fn pairs_descending_from<Q: Ord + ?Sized>(&self, key: &Q) -> impl Iterator<Item = (&Self::Item, &GTree<Self>)>
+    where
+        Self::Item: Borrow<Q>,
+    {
+        let start = self.search_ref(key).map(|(item, _)| item as *const Self::Item);
+        return self.pairs_descending().skip_while(move |(item, _)| start.is_some_and(|start| !std::ptr::eq(*item, start)));
+    }
+    /// Iterate over the left subtrees of all pairs, from the one of the greatest item to the one of the least item. Sets that cannot lend out references to their items cheaply override this.
+    fn left_subtrees_descending(&self) -> impl Iterator<Item = &GTree<Self>> {
+        return self.pairs_descending().map(|(_, left_subtree)| left_subtree);
+    }
+
+    /// Apply `f` to the left subtree of the least item that is greater than or equal to `key`, modifying the set in place. Return false (without calling `f`) if no such item exists.
+    fn update_left_subtree<F: FnOnce(&mut GTree<Self>)>(&mut self, key: &Self::Item, f: F) -> bool {
+        match self.search(key) {
+            None => return false,
+            Some((item, mut left_subtree)) => {
+                f(&mut left_subtree);
+                let (left_set, _, right_set) = self.split(&item);
+                *self = set_join(&left_set, &right_set.insert_min((item, left_subtree)));
+                return true;
+            }
+        }
+    }
+
+    /// Create a set from a non-empty vec of item-left_subtree pairs, sorted by strictly descending item.
+    fn from_descending_pairs(pairs: Vec<(Self::Item, GTree<Self>)>) -> Self {
+        let mut pairs = pairs.into_iter();
+        let mut ret = Self::singleton(pairs.next().expect("Cannot create a set from zero pairs."));
+        for pair in pairs {
+            ret = ret.insert_min(pair);
+        }
+        return ret;
+    }
+
+    /// Return the number of items in the set and in their left subtrees.
+    fn total_len(&self) -> usize {
+        let mut ret = 0;
+        for left_subtree in self.left_subtrees_descending() {
+            ret += 1 + left_subtree.len();
+        }
+        return ret;
+    }
+
+    /// Return the combined summary of all items in the set and in their left subtrees.
+    fn summary(&self) -> Summary<Self> {
+        let mut ret = Summary::<Self>::neutral();
+        // We visit the pairs in descending order, so we prepend the summary of each pair.
+        for (item, left_subtree) in self.pairs_descending_lent() {
+            ret = left_subtree.summary().combine(&summarize::<Self>(&item)).combine(&ret);
+        }
+        return ret;
+    }
+
+    /// Return the combined summary of all pairs whose item lies within the given bounds, in ascending order. The summary of a pair combines the summary of its left subtree with that of its item.
+    fn fold_pairs(&self, lower: Bound<&Self::Item>, upper: Bound<&Self::Item>) -> Summary<Self>
+    where
+        Self::Item: Ord,
+    {
+        let mut ret = Summary::<Self>::neutral();
+        for (item, left_subtree) in self.pairs_descending_lent() {
+            if above_lower(&*item, lower) && below_upper(&*item, upper) {
+                ret = left_subtree.summary().combine(&summarize::<Self>(&item)).combine(&ret);
+            }
+        }
+        return ret;
+    }
+}
+
+// Whether `item` lies on the correct side of a lower bound.
+pub(crate) fn above_lower<I: Ord>(item: &I, lower: Bound<&I>) -> bool {
+    match lower {
+        Bound::Included(lower) => return item >= lower,
+        Bound::Excluded(lower) => return item > lower,
+        Bound::Unbounded => return true,
+    }
+}
+
+// Whether `item` lies on the correct side of an upper bound.
+pub(crate) fn below_upper<I: Ord>(item: &I, upper: Bound<&I>) -> bool {
+    match upper {
+        Bound::Included(upper) => return item <= upper,
+        Bound::Excluded(upper) => return item < upper,
+        Bound::Unbounded => return true,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -62,120 +281,314 @@ pub struct GTreeNode<S: NonemptySet> {
     set: S,
     right: GTree<S>,
     rank: u8,
+    // The number of items in the tree rooted at this node. Unlike the summary, it is always maintained: `GTree::len` relies on it to take constant time. Computing it costs one pass over the set, like computing the summary and the hash.
+    size: usize,
+    summary: Summary<S>, // combined summary of all items in the tree rooted at this node
+    hash: Digest<S>, // Merkle hash of the tree rooted at this node
 }
 
-#[derive(Debug, Clone)]
+impl<S: NonemptySet> GTreeNode<S> {
+    fn new(set: S, right: GTree<S>, rank: u8) -> Self {
+        return GTreeNode {
+            size: set.total_len() + right.len(),
+            summary: set.summary().combine(&right.summary()),
+            hash: merkle::hash_node(&set, &right, rank),
+            set,
+            right,
+            rank,
+        };
+    }
+
+    // Recompute the size, the summary and the hash, after the set or the right subtree have changed.
+    fn refresh(&mut self) {
+        self.size = self.set.total_len() + self.right.len();
+        self.summary = self.set.summary().combine(&self.right.summary());
+        self.hash = merkle::hash_node(&self.set, &self.right, self.rank);
+    }
+
+    /// The inner set of this G-node.
+    pub fn set(&self) -> &S {
+        return &self.set;
+    }
+
+    /// The right subtree of this G-node, holding the items greater than all items of the inner set.
+    pub fn right(&self) -> &GTree<S> {
+        return &self.right;
+    }
+
+    pub fn rank(&self) -> u8 {
+        return self.rank;
+    }
+}
+
+/// The pointer to a G-node of a G-tree over `S`.
+pub type NodePointer<S> = <<S as NonemptySet>::Pointers as PointerFamily>::Pointer<GTreeNode<S>>;
+
+#[derive(Clone)]
 pub enum GTree<S: NonemptySet> {
-    NonEmpty(Rc<GTreeNode<S>>),
+    NonEmpty(NodePointer<S>),
     Empty,
 }
 
-fn update_leftmost<S: NonemptySet>(node: &GTreeNode<S>, leftmost: GTree<S>) -> Rc<GTreeNode<S>> {
-    let ((leftmost_item, _), other_pairs) = node.set.remove_min();
+// Formats exactly like a derived implementation for an `Rc` or `Arc` pointer would.
+impl<S: NonemptySet + Debug> Debug for GTree<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GTree::NonEmpty(node) => return f.debug_tuple("NonEmpty").field(&**node).finish(),
+            GTree::Empty => return f.write_str("Empty"),
+        }
+    }
+}
+
+impl<S: NonemptySet> GTree<S> {
+    /// Return the number of items in the tree, in O(1) time.
+    pub fn len(&self) -> usize {
+        match self {
+            GTree::Empty => return 0,
+            GTree::NonEmpty(node) => return node.size,
+        }
+    }
 
-    return Rc::new(GTreeNode {
-        set: other_pairs.insert_min((leftmost_item, leftmost)),
-        right: node.right.clone(),
-        rank: node.rank,
-    });
+    pub fn is_empty(&self) -> bool {
+        match self {
+            GTree::Empty => return true,
+            GTree::NonEmpty(_) => return false,
+        }
+    }
 }
 
-fn update_right<S: NonemptySet>(node: &GTreeNode<S>, right: GTree<S>) -> Rc<GTreeNode<S>> {
-    return Rc::new(GTreeNode {
-        set: node.set.clone(),
-        right: right,
-        rank: node.rank,
-    });
+// Equality of G-trees compares their shapes: ranks, the items of each G-node, and all subtrees. Subtrees that are shared between both trees are recognized by pointer equality, without comparing their contents.
+//
+// Since G-trees are history independent, two trees with the same items at the same ranks are always equal. To compare only the items, use `GTree::content_eq`.
+impl<S: NonemptySet + PartialEq> PartialEq for GTree<S> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (GTree::Empty, GTree::Empty) => return true,
+            (GTree::NonEmpty(a), GTree::NonEmpty(b)) => return S::Pointers::ptr_eq(a, b) || **a == **b,
+            _ => return false,
+        }
+    }
+}
+
+impl<S: NonemptySet + Eq> Eq for GTree<S> {}
+
+impl<S: NonemptySet + Hash> Hash for GTree<S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            GTree::Empty => 0u8.hash(state),
+            GTree::NonEmpty(node) => {
+                1u8.hash(state);
+                node.hash(state);
+            }
+        }
+    }
+}
+
+// The cached size and summary are determined by the other fields, so they are neither compared nor hashed (except for comparing the sizes as a cheap early exit).
+impl<S: NonemptySet + PartialEq> PartialEq for GTreeNode<S> {
+    fn eq(&self, other: &Self) -> bool {
+        return self.rank == other.rank && self.size == other.size && self.set == other.set && self.right == other.right;
+    }
+}
+
+impl<S: NonemptySet + Eq> Eq for GTreeNode<S> {}
+
+impl<S: NonemptySet + Hash> Hash for GTreeNode<S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank.hash(state);
+        self.set.hash(state);
+        self.right.hash(state);
+    }
+}
+
+impl<S: NonemptySet> GTree<S> {
+    /// Return whether both trees contain the same items, regardless of their ranks and the shapes of the trees.
+    pub fn content_eq<S2: NonemptySet<Item = S::Item>>(&self, other: &GTree<S2>) -> bool
+    where
+        S::Item: PartialEq,
+    {
+        return self.len() == other.len() && self.iter().eq(other.iter());
+    }
+}
+
+fn update_right<S: NonemptySet>(node: &GTreeNode<S>, right: GTree<S>) -> NodePointer<S> {
+    return S::Pointers::new(GTreeNode::new(
+        node.set.clone(),
+        right,
+        node.rank,
+    ));
 }
 
 // A (non-empty) GTree has a root GTreeNode that consists of a rank, a right subtree, and a non-empty set of pairs of items and their left subtrees.
 // Occasionally, we need to construct a nonempty GTree from a rank, a right subtree, and a *possibly empty* set of pairs of items and their left subtrees. In those cases, if the set is empty, the resulting GTree is simply the supplied right subtree.
-fn lift<S: NonemptySet>(s: &Set<S>, right: GTree<S>, rank: u8) -> GTree<S> {
+fn lift<S: NonemptySet>(s: Set<S>, right: GTree<S>, rank: u8) -> GTree<S> {
     match s {
         Set::Empty => return right,
         Set::NonEmpty(set) => {
-            return GTree::NonEmpty(Rc::new(GTreeNode {
-                rank,
-                set: set.clone(),
+            return GTree::NonEmpty(S::Pointers::new(GTreeNode::new(
+                set,
                 right,
-            }))
+                rank,
+            )))
         }
     };
 }
 
+// Unzipping and zipping take apart the G-nodes along a search path and build new ones from the pieces. The pieces of a G-node that nobody else references can be moved out of it (via the consuming methods of `NonemptySet`), while the pieces of a shared G-node must be cloned. The functions below take their trees by value and check for every G-node whether they hold the only reference to it. The borrowing public functions hand them clones of their arguments, so they never move anything out of the caller's trees, but `insert` and `delete` pass the freshly unzipped trees on to `zip2_owned` and `zip3_owned`, which can then consume the G-nodes that unzipping has just created.
+
+// A G-node that is about to be taken apart: `Ok` if we held the only reference to it, `Err` if it is shared.
+type TakenNode<S> = Result<GTreeNode<S>, NodePointer<S>>;
+
+fn take_node<S: NonemptySet>(node: NodePointer<S>) -> TakenNode<S> {
+    return S::Pointers::try_unwrap(node);
+}
+
+// Internal helper function: report a visit of a G-node to the I/O cost model, and pass the G-node on.
+fn visit_pointer<S: NonemptySet>(node: NodePointer<S>) -> NodePointer<S> {
+    iocost::read(&*node);
+    return node;
+}
+
+// Internal helper function: like `visit_pointer`, but for a borrowed G-node.
+fn visit<S: NonemptySet>(node: &GTreeNode<S>) -> &GTreeNode<S> {
+    iocost::read(node);
+    return node;
+}
+
+// The pieces of a G-node whose set has been split: the lesser set, the pair of the item that was split at (if any), the greater set, and the right subtree and the rank of the G-node.
+type SplitNode<S> = (Set<S>, Option<Pair<S>>, Set<S>, GTree<S>, u8);
+
+// Split the set of a taken G-node at `key`, and also return the right subtree and the rank of the G-node.
+fn split_taken<S: NonemptySet>(node: TakenNode<S>, key: &S::Item) -> SplitNode<S> {
+    match node {
+        Ok(node) => {
+            let (left_set, found, right_set) = node.set.split_owned(key);
+            return (left_set, found, right_set, node.right, node.rank);
+        }
+        Err(node) => {
+            let (left_set, found, right_set) = split_with_item(&node.set, key);
+            return (left_set, found, right_set, node.right.clone(), node.rank);
+        }
+    }
+}
+
+// Remove the least pair from the set of a taken G-node, and also return the right subtree and the rank of the G-node.
+fn remove_min_taken<S: NonemptySet>(node: TakenNode<S>) -> (Pair<S>, Set<S>, GTree<S>, u8) {
+    match node {
+        Ok(node) => {
+            let (min, others) = node.set.remove_min_owned();
+            return (min, others, node.right, node.rank);
+        }
+        Err(node) => {
+            let (min, others) = node.set.remove_min();
+            return (min, others, node.right.clone(), node.rank);
+        }
+    }
+}
+
 pub fn unzip<S: NonemptySet + Debug>(t: &GTree<S>, key: &S::Item) -> (GTree<S>, GTree<S>) {
+    let (left, _, right) = unzip_find(t, key);
+    return (left, right);
+}
+
+/// Like `unzip`, but additionally return the item of `t` that is equal to `key` (if any), together with its rank.
+pub fn unzip_find<S: NonemptySet + Debug>(
+    t: &GTree<S>,
+    key: &S::Item,
+) -> (GTree<S>, Option<RankedItem<S>>, GTree<S>) {
+    return unzip_find_owned(t.clone(), key);
+}
+
+/// Like `unzip_find`, but consume the tree, moving the pieces out of all G-nodes that `t` holds the only reference to.
+pub(crate) fn unzip_find_owned<S: NonemptySet>(
+    t: GTree<S>,
+    key: &S::Item,
+) -> (GTree<S>, Option<RankedItem<S>>, GTree<S>) {
     match t {
         // Empty tree is trivial to unzip.
-        GTree::Empty => return (GTree::Empty, GTree::Empty),
+        GTree::Empty => return (GTree::Empty, None, GTree::Empty),
 
-        GTree::NonEmpty(s) => match s.set.split(key) {
+        GTree::NonEmpty(node) => match split_taken(take_node(visit_pointer::<S>(node)), key) {
             // If the current node contain the split point, everything until the split point becomes the left return, with the left child of the split point turning into the right child of the left return. Everything after the split point becomes the right return, with the right child of the current node becoming the right child of the right return.
-            (left_set, Some(left_subtree_of_key), right_set) => {
+            (left_set, Some((found, left_subtree_of_key)), right_set, right, rank) => {
                 return (
-                    lift(&left_set, left_subtree_of_key, s.rank),
-                    lift(&right_set, s.right.clone(), s.rank),
+                    lift(left_set, left_subtree_of_key, rank),
+                    Some((found, rank)),
+                    lift(right_set, right, rank),
                 );
             }
 
-            (_, None, Set::Empty) => {
+            (left_set, None, Set::Empty, right, rank) => {
                 // If the current node does not contain the split point, and all its items are less than the split point, then recursively split its right child (and replace it with its left recursive return).
-                let (left, right) = unzip(&s.right, key);
-                return (GTree::NonEmpty(update_right(s, left)), right);
+                let (left, found, right) = unzip_find_owned(right, key);
+                return (lift(left_set, left, rank), found, right);
             }
 
-            (left_set, None, Set::NonEmpty(r)) => {
-                // println!("just_split {:#?}\n{:#?}", left_set, r);
+            (left_set, None, Set::NonEmpty(r), right, rank) => {
                 // If the current node does not contain the split point, but it does contain items greater than the split point, we need to split in the leftmost child of those greater items.
-                let ((r_leftmost_item, r_leftmost_subtree), r_remaining) = r.remove_min();
-                let (left, right) = unzip(&r_leftmost_subtree, key);
-                // println!("recursive unzip {:#?}\n{:#?}", left, right);
-                // println!("split returning\n{:#?}\n{:#?}", lift(&left_set, left.clone(), s.rank), GTree::NonEmpty(update_leftmost(s, right.clone())));
-                // return (
-                //     lift(&left_set, left.clone(), s.rank),
-                //     GTree::NonEmpty(update_leftmost(s, right)),
-                // );
-                let right_return = GTree::NonEmpty(Rc::new(GTreeNode {
-                    rank: s.rank,
-                    set: r_remaining.insert_min((r_leftmost_item, right)),
-                    right: s.right.clone(),
-                }));
-                // let right_return = GTree::NonEmpty(update_leftmost(&GTreeNode {
-                //     set: r,
-                //     rank: s.rank,
-                //     right: s.right.clone(),
-                // }, right));
-                // println!("split returning\n{:#?}\n{:#?}", lift(&left_set, left.clone(), s.rank), right_return);
-                return (lift(&left_set, left.clone(), s.rank), right_return);
+                let ((r_leftmost_item, r_leftmost_subtree), r_remaining) = r.remove_min_owned();
+                let (left, found, greater) = unzip_find_owned(r_leftmost_subtree, key);
+                let right_return = GTree::NonEmpty(S::Pointers::new(GTreeNode::new(
+                    r_remaining.insert_min_owned((r_leftmost_item, greater)),
+                    right,
+                    rank,
+                )));
+                return (lift(left_set, left, rank), found, right_return);
             }
         },
     }
 }
 
 pub fn zip2<S: NonemptySet>(left: &GTree<S>, right: &GTree<S>) -> GTree<S> {
+    return zip2_owned(left.clone(), right.clone());
+}
+
+/// Like `zip2`, but consume the trees, moving the pieces out of all G-nodes that they hold the only reference to.
+pub(crate) fn zip2_owned<S: NonemptySet>(left: GTree<S>, right: GTree<S>) -> GTree<S> {
     match (left, right) {
-        (GTree::Empty, _) => return right.clone(),
-        (_, GTree::Empty) => return left.clone(),
+        (GTree::Empty, right) => return right,
+        (left, GTree::Empty) => return left,
         (GTree::NonEmpty(l), GTree::NonEmpty(r)) => {
+            let (l, r) = (visit_pointer::<S>(l), visit_pointer::<S>(r));
             if l.rank < r.rank {
                 // Zip l into the leftmost subtree of r.
-                let ((_, r_leftmost_subtree), _) = r.set.remove_min();
-                let zipped = zip2(left, &r_leftmost_subtree);
-                return GTree::NonEmpty(update_leftmost(r, zipped));
+                let ((r_leftmost_item, r_leftmost_subtree), r_others, r_right, r_rank) = remove_min_taken(take_node(r));
+                let zipped = zip2_owned(GTree::NonEmpty(l), r_leftmost_subtree);
+                return GTree::NonEmpty(S::Pointers::new(GTreeNode::new(
+                    r_others.insert_min_owned((r_leftmost_item, zipped)),
+                    r_right,
+                    r_rank,
+                )));
             } else if l.rank > r.rank {
                 // Zip r into the right subtree of l.
-                let zipped = zip2(&l.right, right);
-                return GTree::NonEmpty(update_right(l, zipped));
+                match take_node(l) {
+                    Ok(l) => {
+                        let zipped = zip2_owned(l.right, GTree::NonEmpty(r));
+                        return GTree::NonEmpty(S::Pointers::new(GTreeNode::new(l.set, zipped, l.rank)));
+                    }
+                    Err(l) => {
+                        let zipped = zip2_owned(l.right.clone(), GTree::NonEmpty(r));
+                        return GTree::NonEmpty(update_right(&l, zipped));
+                    }
+                }
             } else {
                 // Equal ranks. Join the two inner sets, with the right subtree of the left node being zipped into the leftmost subtree of the right node.
-                let ((r_leftmost_item, r_leftmost_subtree), r_others) = r.set.remove_min();
-                let zipped = zip2(&l.right, &r_leftmost_subtree);
-                let right_set = r_others.insert_min((r_leftmost_item, zipped));
-
-                return GTree::NonEmpty(Rc::new(GTreeNode {
-                    rank: l.rank, // same as r.rank
-                    set: NonemptySet::join(&l.set, &right_set),
-                    right: r.right.clone(),
-                }));
+                let ((r_leftmost_item, r_leftmost_subtree), r_others, r_right, rank) = remove_min_taken(take_node(r));
+                let joined = match take_node(l) {
+                    Ok(l) => {
+                        let zipped = zip2_owned(l.right, r_leftmost_subtree);
+                        S::join_owned(l.set, r_others.insert_min_owned((r_leftmost_item, zipped)))
+                    }
+                    Err(l) => {
+                        let zipped = zip2_owned(l.right.clone(), r_leftmost_subtree);
+                        NonemptySet::join(&l.set, &r_others.insert_min_owned((r_leftmost_item, zipped)))
+                    }
+                };
+                return GTree::NonEmpty(S::Pointers::new(GTreeNode::new(
+                    joined,
+                    r_right,
+                    rank, // same as the rank of l
+                )));
             }
         }
     }
@@ -187,18 +600,28 @@ pub fn zip3<S: NonemptySet>(
     rank: u8,
     right: &GTree<S>,
 ) -> GTree<S> {
-    let mid = GTree::NonEmpty(Rc::new(GTreeNode {
+    return zip3_owned(left.clone(), item, rank, right.clone());
+}
+
+/// Like `zip3`, but consume the trees, moving the pieces out of all G-nodes that they hold the only reference to.
+pub(crate) fn zip3_owned<S: NonemptySet>(
+    left: GTree<S>,
+    item: S::Item,
+    rank: u8,
+    right: GTree<S>,
+) -> GTree<S> {
+    let mid = GTree::NonEmpty(S::Pointers::new(GTreeNode::new(
+        S::singleton((item, GTree::Empty)),
+        GTree::Empty,
         rank,
-        set: S::singleton((item, GTree::Empty)),
-        right: GTree::Empty,
-    }));
-    return zip2(&zip2(&left, &mid), &right);
+    )));
+    return zip2_owned(zip2_owned(left, mid), right);
 }
 
 pub fn insert<S: NonemptySet + Debug>(t: &GTree<S>, item: S::Item, rank: u8) -> GTree<S> {
     let (left, right) = unzip(t, &item);
-    let zipped = zip3(&left, item, rank, &right);
-    return zipped;
+    // The spines of `left` and `right` consist of new G-nodes, which zipping can consume.
+    return zip3_owned(left, item, rank, right);
 }
 
 pub fn insert_explicit<S: NonemptySet + Debug>(t: &GTree<S>, item: S::Item, rank: u8) -> GTree<S> {
@@ -206,11 +629,11 @@ pub fn insert_explicit<S: NonemptySet + Debug>(t: &GTree<S>, item: S::Item, rank
     // println!("calling insert_explicit for {:?}", t);
     match t {
         GTree::Empty => {
-            return GTree::NonEmpty(Rc::new(GTreeNode {
+            return GTree::NonEmpty(S::Pointers::new(GTreeNode::new(
+                S::singleton((item, GTree::Empty)),
+                GTree::Empty,
                 rank,
-                set: S::singleton((item, GTree::Empty)),
-                right: GTree::Empty,
-            }))
+            )))
         }
         GTree::NonEmpty(s) => match s.set.split(&item) {
             (_, Some(_), _) => {
@@ -222,32 +645,32 @@ pub fn insert_explicit<S: NonemptySet + Debug>(t: &GTree<S>, item: S::Item, rank
                 // All items in `s` are strictly less than `item`.
 
                 if rank < s.rank {
-                    return GTree::NonEmpty(Rc::new(GTreeNode {
-                        set: s.set.clone(),
-                        right: insert_explicit(&s.right, item, rank),
-                        rank: s.rank,
-                    }));
+                    return GTree::NonEmpty(S::Pointers::new(GTreeNode::new(
+                        s.set.clone(),
+                        insert_explicit(&s.right, item, rank),
+                        s.rank,
+                    )));
                 } else if rank == s.rank {
                     // Insert `item` into the current node as the greatest item.
                     // Unzip right subtree at `item` to obtain the left subtree of `item` and the new right subtree.
                     let (l, r) = unzip(&s.right, &item);
-                    return GTree::NonEmpty(Rc::new(GTreeNode {
-                        set: set_join(&left_set, &S::singleton((item, l))), // Principled solution is adding a `insert_max` method to `NonemptySet`.
-                        right: r,
-                        rank: s.rank,
-                    }));
+                    return GTree::NonEmpty(S::Pointers::new(GTreeNode::new(
+                        set_join(&left_set, &S::singleton((item, l))), // Principled solution is adding a `insert_max` method to `NonemptySet`.
+                        r,
+                        s.rank,
+                    )));
                 } else
                 /* rank > s.rank */
                 {
                     // `item` becomes a singleton node. Split the right subtree of `s` at `item`. The lesser tree becomes the new right subtree of left_set to form the left subtree of the singleton node. The greater tree becomes the right subtree of the singleton node.
                     let (l, r) = unzip(&s.right, &item);
-                    let left_subtree = lift(&left_set, l, s.rank);
+                    let left_subtree = lift(left_set, l, s.rank);
 
-                    return GTree::NonEmpty(Rc::new(GTreeNode {
+                    return GTree::NonEmpty(S::Pointers::new(GTreeNode::new(
+                        S::singleton((item, left_subtree)),
+                        r,
                         rank,
-                        set: S::singleton((item, left_subtree)),
-                        right: r,
-                    }));
+                    )));
                 }
             }
 
@@ -261,11 +684,11 @@ pub fn insert_explicit<S: NonemptySet + Debug>(t: &GTree<S>, item: S::Item, rank
                     let new_subtree = insert_explicit(&leftmost_subtree, item, rank);
                     let new_right = others.insert_min((leftmost_item, new_subtree));
 
-                    return GTree::NonEmpty(Rc::new(GTreeNode {
-                        set: set_join(&left_set, &new_right),
-                        right: s.right.clone(),
-                        rank: s.rank,
-                    }));
+                    return GTree::NonEmpty(S::Pointers::new(GTreeNode::new(
+                        set_join(&left_set, &new_right),
+                        s.right.clone(),
+                        s.rank,
+                    )));
                 } else if rank == s.rank {
                     // Insert `item` into the right_set as a least node (that steals all items from the leftmost subtree of right_set that are less than itself).
                     // The join left_set and the new right_set back together.
@@ -273,39 +696,59 @@ pub fn insert_explicit<S: NonemptySet + Debug>(t: &GTree<S>, item: S::Item, rank
                     // Update the leftmost subtree of `others`, and then prepend `item`.
                     let new_right = others.insert_min((leftmost_item, r)).insert_min((item, l));
 
-                    return GTree::NonEmpty(Rc::new(GTreeNode {
-                        set: set_join(&left_set, &new_right),
-                        right: s.right.clone(),
-                        rank: s.rank,
-                    }));
+                    return GTree::NonEmpty(S::Pointers::new(GTreeNode::new(
+                        set_join(&left_set, &new_right),
+                        s.right.clone(),
+                        s.rank,
+                    )));
                 } else
                 /* rank > s.rank */
                 {
                     // `item` becomes a singleton node. Split the leftmost subtree of `right_set` at `item`. The lesser tree becomes the right subtree of left_set to form the left subtree of the singleton node. The greater tree becomes the new leftmost subtree of `right_set`, which together with `s.right` becomes the right subtree of the singleton node.
                     let (l, r) = unzip(&leftmost_subtree, &item);
 
-                    let left_subtree = lift(&left_set, l, s.rank);
-                    let right_subtree = GTree::NonEmpty(Rc::new(GTreeNode {
-                        set: others.insert_min((leftmost_item, r)),
-                        right: s.right.clone(),
-                        rank: s.rank,
-                    }));
+                    let left_subtree = lift(left_set, l, s.rank);
+                    let right_subtree = GTree::NonEmpty(S::Pointers::new(GTreeNode::new(
+                        others.insert_min((leftmost_item, r)),
+                        s.right.clone(),
+                        s.rank,
+                    )));
 
-                    return GTree::NonEmpty(Rc::new(GTreeNode {
+                    return GTree::NonEmpty(S::Pointers::new(GTreeNode::new(
+                        S::singleton((item, left_subtree)),
+                        right_subtree,
                         rank,
-                        set: S::singleton((item, left_subtree)),
-                        right: right_subtree,
-                    }));
+                    )));
                 }
             }
         },
     }
 }
 
+/// Insert `item` at the rank assigned to it by `ranker`.
+pub fn insert_ranked<S: NonemptySet + Debug, R: RankDerivation<S::Item>>(
+    t: &GTree<S>,
+    item: S::Item,
+    ranker: &R,
+) -> GTree<S> {
+    let rank = ranker.rank(&item);
+    return insert(t, item, rank);
+}
+
+impl<S: NonemptySet + Debug> GTree<S>
+where
+    S::Item: Hash,
+{
+    /// Insert `item` at a rank derived from its hash, targeting G-nodes of size `S::TARGET_NODE_SIZE`.
+    pub fn insert(&self, item: S::Item) -> GTree<S> {
+        return insert_ranked(self, item, &HashRank::new(S::TARGET_NODE_SIZE));
+    }
+}
+
 pub fn delete<S: NonemptySet + Debug>(t: &GTree<S>, item: &S::Item) -> GTree<S> {
     // println!("calling delete for {:?}", t);
     let (left, right) = unzip(t, item);
-    return zip2(&left, &right);
+    return zip2_owned(left, right);
 }
 
 pub fn delete_explicit<S: NonemptySet + Debug>(t: &GTree<S>, item: &S::Item) -> GTree<S> {
@@ -317,14 +760,14 @@ pub fn delete_explicit<S: NonemptySet + Debug>(t: &GTree<S>, item: &S::Item) ->
                 // Its left subtree becomes the right child of a GTree node together with `left_set`, and `right_set` becomes a GTree node together with the right subtree of `s`.
                 // Zip together these two trees to obtain a tree that contains all items of `s` except `item`.
                 return zip2(
-                    &lift(&left_set, left_subtree_of_key, s.rank),
-                    &lift(&right_set, s.right.clone(), s.rank),
+                    &lift(left_set, left_subtree_of_key, s.rank),
+                    &lift(right_set, s.right.clone(), s.rank),
                 );
             }
             (left_set, None, Set::Empty) => {
                 // The set in `s` did not contain `item`, nor any greater items.
                 // Hence, `item` must be in the right subtree (if at all).
-                return lift(&left_set, delete_explicit(&s.right, item), s.rank);
+                return lift(left_set, delete_explicit(&s.right, item), s.rank);
             }
             (left_set, None, Set::NonEmpty(right_set)) => {
                 // The set in `s` did not contain `item`.
@@ -333,29 +776,104 @@ pub fn delete_explicit<S: NonemptySet + Debug>(t: &GTree<S>, item: &S::Item) ->
                 let new_subtree = delete_explicit(&leftmost_subtree, item);
                 let new_right = others.insert_min((leftmost_item, new_subtree));
 
-                return GTree::NonEmpty(Rc::new(GTreeNode {
-                    set: set_join(&left_set, &new_right),
-                    right: s.right.clone(),
-                    rank: s.rank,
-                }));
+                return GTree::NonEmpty(S::Pointers::new(GTreeNode::new(
+                    set_join(&left_set, &new_right),
+                    s.right.clone(),
+                    s.rank,
+                )));
             }
         },
     }
 }
 
-pub fn has<S: NonemptySet>(t: &GTree<S>, key: &S::Item) -> bool
+/// Replace the item in `t` that is equal to `item` with `item` itself, keeping the shape of the tree. Return the new tree and the replaced item, or a clone of `t` and `None` if `t` contains no item equal to `item`.
+///
+/// This is only interesting for item types whose `Ord` implementation does not consider all of their data, such as the `KeyValue`s of maps.
+pub fn replace<S: NonemptySet>(t: &GTree<S>, item: S::Item) -> (GTree<S>, Option<S::Item>)
 where
-    S::Item: Ord,
+    S::Item: Clone + Ord,
+{
+    match t {
+        GTree::Empty => return (GTree::Empty, None),
+        GTree::NonEmpty(node) => match node.set.search(&item) {
+            None => {
+                // All items in the node are less than `item`, so continue in the right subtree.
+                let (new_right, old) = replace(&node.right, item);
+                match old {
+                    None => return (t.clone(), None),
+                    Some(old) => return (GTree::NonEmpty(update_right(node, new_right)), Some(old)),
+                }
+            }
+            Some((found, left_subtree)) => {
+                if found == item {
+                    // Swap out the item itself, it keeps its left subtree.
+                    let new_set = replace_pair(&node.set, &found, (item, left_subtree));
+                    return (
+                        GTree::NonEmpty(S::Pointers::new(GTreeNode::new(
+                            new_set,
+                            node.right.clone(),
+                            node.rank,
+                        ))),
+                        Some(found),
+                    );
+                } else {
+                    // `item` can only be in the left subtree of the least greater item.
+                    let (new_left, old) = replace(&left_subtree, item);
+                    match old {
+                        None => return (t.clone(), None),
+                        Some(old) => {
+                            let new_set = replace_pair(&node.set, &found, (found.clone(), new_left));
+                            return (
+                                GTree::NonEmpty(S::Pointers::new(GTreeNode::new(
+                                    new_set,
+                                    node.right.clone(),
+                                    node.rank,
+                                ))),
+                                Some(old),
+                            );
+                        }
+                    }
+                }
+            }
+        },
+    }
+}
+
+// Replace the pair whose item is equal to `key` (which must be in `s`) with `new_pair`.
+fn replace_pair<S: NonemptySet>(s: &S, key: &S::Item, new_pair: (S::Item, GTree<S>)) -> S {
+    let (left_set, _, right_set) = s.split(key);
+    return set_join(&left_set, &right_set.insert_min(new_pair));
+}
+
+/// Return whether the tree contains an item equal to `key`.
+pub fn has<S: NonemptySet, Q: Ord + ?Sized>(t: &GTree<S>, key: &Q) -> bool
+where
+    S::Item: Borrow<Q>,
 {
     match t {
         GTree::Empty => return false,
-        GTree::NonEmpty(node) => match node.set.search(key) {
+        GTree::NonEmpty(node) => match visit(node).set.search_left_subtree(key) {
             None => return has(&node.right, key),
-            Some(yay) => {
-                if &yay.0 == key {
-                    return true;
+            Some((_, true)) => return true,
+            Some((left_subtree, false)) => return has(left_subtree, key),
+        },
+    }
+}
+
+/// Return a reference to the item in the tree that is equal to `key`, if any.
+pub fn get<'a, S: NonemptySet, Q: Ord + ?Sized>(t: &'a GTree<S>, key: &Q) -> Option<&'a S::Item>
+where
+    S::Item: Borrow<Q>,
+{
+    match t {
+        GTree::Empty => return None,
+        GTree::NonEmpty(node) => match visit(node).set.search_ref(key) {
+            None => return get(&node.right, key),
+            Some((item, left_subtree)) => {
+                if item.borrow() == key {
+                    return Some(item);
                 } else {
-                    return has(&yay.1, key);
+                    return get(left_subtree, key);
                 }
             }
         },
@@ -374,7 +892,7 @@ where
     /// Get the number of items in the set.
     fn len(&self) -> usize;
     // Get an item and its left subtree by index, where index 0 denotes the least item.
-    fn get_pair_by_index(&self, index: usize) -> Option<&(Self::Item, GTree<Self>)>;
+    fn get_pair_by_index(&self, index: usize) -> Option<(&Self::Item, &GTree<Self>)>;
     // Get an item by index, where index 0 denotes the least item.
     fn get_by_index(&self, index: usize) -> Option<&Self::Item> {
         return self.get_pair_by_index(index).map(|(item, _)| item);
@@ -383,21 +901,22 @@ where
     fn from_descending(items: &[Self::Item]) -> Self;
     // Total number of items this could store without allocating more memory. Used to compute space amplification.
     fn item_slot_count(&self) -> usize;
+    // Number of bytes this set has allocated in addition to the G-node that contains it (not counting the G-nodes of its left subtrees). Used by `memory::memory_report`.
+    fn heap_bytes(&self) -> usize;
+    // The internal vertices of the set in the order in which they are linked, each given by an address (shared vertices have the same address) and its items in storage order. Used for visualization. By default, the set consists of a single vertex.
+    fn vertex_chain(&self) -> Vec<(*const (), Vec<&Self::Item>)> {
+        let items = (0..self.len()).filter_map(|i| self.get_by_index(i)).collect();
+        return vec![(self as *const Self as *const (), items)];
+    }
 }
 
-// Return a vec of item-left_subtree pairs in descending order.
-fn pairs_ascending<S: NonemptySetMeta>(s: &S) -> Vec<&(S::Item, GTree<S>)>
+// Return a vec of item-left_subtree pairs in ascending order.
+fn pairs_ascending<S: NonemptySetMeta>(s: &S) -> Vec<(Lent<'_, S::Item>, &GTree<S>)>
 where
     S::Item: Ord,
 {
-    let mut ret = vec![];
-
-    for i in 0..s.len() {
-        let pair = s.get_pair_by_index(i).unwrap();
-        ret.push(pair);
-    }
-
-    ret.sort_by(|(item_a, _), (item_b, _)| item_a.cmp(item_b));
+    let mut ret: Vec<_> = s.pairs_descending_lent().collect();
+    ret.reverse();
     return ret;
 }
 
@@ -547,7 +1066,7 @@ where
              */
             // Right descendents are greater than the greatest item in the node.
             if let Some(ref least) = right_stats.least_item {
-                if least <= pair_stats[pair_stats.len() - 1].0 {
+                if least <= &*pair_stats[pair_stats.len() - 1].0 {
                     stats.is_search_tree = false;
                     println!("\n\n search tree property: right too great\n{:#?}\n\n", t);
                 }
@@ -555,7 +1074,7 @@ where
             for (i, (item, left_subtree_stats)) in pair_stats.iter().enumerate() {
                 if let Some(ref least) = left_subtree_stats.least_item {
                     // All left descendents are greater than their parent's left sibling
-                    if i > 0 && least <= pair_stats[i - 1].0 {
+                    if i > 0 && least <= &*pair_stats[i - 1].0 {
                         stats.is_search_tree = false;
                         println!(
                             "\n\n search tree property: left {} too small\n{:#?}\n\n",
@@ -567,7 +1086,7 @@ where
 
                 if let Some(ref greatest) = left_subtree_stats.greatest_item {
                     // All left descendents are less than their parent
-                    if greatest >= item {
+                    if greatest >= &**item {
                         stats.is_search_tree = false;
                         println!(
                             "\n\n search tree property: left {} too great\n{:#?}\n\n",
@@ -581,11 +1100,11 @@ where
             let least_pair = &pair_stats[0];
             match least_pair.1.least_item {
                 Some(ref least) => stats.least_item = Some(least.clone()),
-                None => stats.least_item = Some(least_pair.0.clone()),
+                None => stats.least_item = Some((*least_pair.0).clone()),
             }
             match right_stats.greatest_item {
                 Some(greatest) => stats.greatest_item = Some(greatest.clone()),
-                None => stats.greatest_item = Some(pair_stats[pair_stats.len() - 1].0.clone()),
+                None => stats.greatest_item = Some((*pair_stats[pair_stats.len() - 1].0).clone()),
             }
 
             return stats;
@@ -637,11 +1156,14 @@ pub fn possibly_empty_sets_assert_eq<
 /*
 Implementation of NonemptySet for a sorted (in descending order) Vec for testing purposes.
 */
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ControlSet<I: Clone + Ord>(pub Vec<(I, GTree<Self>)>);
 
 impl<I: Clone + Ord> NonemptySet for ControlSet<I> {
     type Item = I;
+    type Pointers = RcFamily;
+    type Augmentation = ();
+    type Hasher = ();
 
     fn singleton(item: (Self::Item, GTree<Self>)) -> Self {
         return ControlSet(vec![item]);
@@ -720,16 +1242,89 @@ impl<I: Clone + Ord> NonemptySet for ControlSet<I> {
         return new;
     }
 
+    fn split_owned(mut self, key: &Self::Item) -> (Set<Self>, Option<(Self::Item, GTree<Self>)>, Set<Self>) {
+        let (left, found) = match self.0.binary_search_by(|(my_item, _)| key.cmp(my_item)) {
+            Ok(i) => {
+                let left = self.0.split_off(i + 1);
+                (left, self.0.pop())
+            }
+            Err(i) => (self.0.split_off(i), None),
+        };
+        return (
+            if left.len() == 0 {
+                Set::Empty
+            } else {
+                Set::NonEmpty(ControlSet(left))
+            },
+            found,
+            if self.0.len() == 0 {
+                Set::Empty
+            } else {
+                Set::NonEmpty(self)
+            },
+        );
+    }
+
+    fn join_owned(left: Self, mut right: Self) -> Self {
+        right.0.extend(left.0);
+        return right;
+    }
+
+    fn remove_min_owned(mut self) -> ((Self::Item, GTree<Self>), Set<Self>) {
+        let popped = self.0.pop().unwrap();
+        return (
+            popped,
+            if self.0.len() == 0 {
+                Set::Empty
+            } else {
+                Set::NonEmpty(self)
+            },
+        );
+    }
+
     fn search(&self, key: &Self::Item) -> Option<(Self::Item, GTree<Self>)> {
-        match self.0.binary_search_by(|x| key.cmp(&x.0)) {
+        return self.search_by(|my_item| key.cmp(my_item));
+    }
+
+    fn search_ref<Q: Ord + ?Sized>(&self, key: &Q) -> Option<(&Self::Item, &GTree<Self>)>
+    where
+        Self::Item: Borrow<Q>,
+    {
+        return self.search_ref_by(|my_item| key.cmp(my_item.borrow())).map(|(item, subtree)| (item, subtree));
+    }
+
+    fn update_left_subtree<F: FnOnce(&mut GTree<Self>)>(&mut self, key: &Self::Item, f: F) -> bool {
+        let index = match self.0.binary_search_by(|(my_item, _)| key.cmp(my_item)) {
+            Ok(i) => i,
+            Err(0) => return false,
+            Err(i) => i - 1,
+        };
+        f(&mut self.0[index].1);
+        return true;
+    }
+
+    fn pairs_descending(&self) -> impl Iterator<Item = (&Self::Item, &GTree<Self>)> {
+        return self.0.iter().map(|(item, subtree)| (item, subtree));
+    }
+}
+
+impl<I: Clone + Ord> ControlSet<I> {
+    // The implementation of `search`, but with the comparison against the key supplied as a function.
+    fn search_by<F: Fn(&I) -> std::cmp::Ordering>(&self, cmp: F) -> Option<(I, GTree<Self>)> {
+        return self.search_ref_by(cmp).cloned();
+    }
+
+    // The implementation of `search_ref`, with the comparison against the key supplied as a function.
+    fn search_ref_by<F: Fn(&I) -> std::cmp::Ordering>(&self, cmp: F) -> Option<&(I, GTree<Self>)> {
+        match self.0.binary_search_by(|x| cmp(&x.0)) {
             Ok(i) => {
-                return Some(self.0[i].clone());
+                return Some(&self.0[i]);
             }
             Err(i) => {
                 if i == 0 {
                     return None;
                 } else {
-                    return Some(self.0[i - 1].clone());
+                    return Some(&self.0[i - 1]);
                 }
             }
         }
@@ -751,8 +1346,8 @@ impl<I: Clone + Ord + Debug> NonemptySetMeta for ControlSet<I> {
         return self.0.len();
     }
 
-    fn get_pair_by_index(&self, index: usize) -> Option<&(Self::Item, GTree<Self>)> {
-        return self.0.get(self.0.len() - (index + 1));
+    fn get_pair_by_index(&self, index: usize) -> Option<(&Self::Item, &GTree<Self>)> {
+        return self.0.get(self.0.len() - (index + 1)).map(|(item, subtree)| (item, subtree));
     }
 
     fn from_descending(items: &[Self::Item]) -> Self {
@@ -772,8 +1367,15 @@ impl<I: Clone + Ord + Debug> NonemptySetMeta for ControlSet<I> {
     fn item_slot_count(&self) -> usize {
         return self.len();
     }
+
+    fn heap_bytes(&self) -> usize {
+        return self.0.capacity() * std::mem::size_of::<(I, GTree<Self>)>();
+    }
 }
 
+/// A ControlSet that can be used as the inner set of a `GTreeMap`.
+pub type ControlMap<K, V> = ControlSet<KeyValue<K, V>>;
+
 // Operations for constructing simple random sets. The subtrees in those sets are always empty.
 #[derive(Debug, Arbitrary, Clone)]
 pub enum SetCreationOperation<Item> {
@@ -820,37 +1422,44 @@ pub enum TreeCreation<Item> {
     Empty,
     Insert(Box<Self>, Item, u8),
     InsertExplicit(Box<Self>, Item, u8),
+    InsertHashRank(Box<Self>, Item),
     Remove(Box<Self>, Item),
     RemoveExplicit(Box<Self>, Item),
 }
 
-// Create a tree according to a TreeDescription value.
-pub fn create_tree<Item: Clone + Ord, S: NonemptySet<Item = Item> + Debug>(
+// Create a tree according to a TreeDescription value, validating the tree after each operation.
+pub fn create_tree<Item: Clone + Ord + Hash + Debug, S: NonemptySetMeta<Item = Item>>(
     creation: TreeCreation<Item>,
-) -> GTree<S> {
-    match creation {
-        TreeCreation::Empty => return GTree::Empty,
+) -> GTree<S>
+where
+    Summary<S>: PartialEq,
+{
+    let new_tree = match creation {
+        TreeCreation::Empty => GTree::Empty,
         TreeCreation::Insert(creation_rec, item, rank) => {
             let tree_rec = create_tree(*creation_rec);
-            let new_tree = insert(&tree_rec, item.clone(), rank);
-            return new_tree;
+            insert(&tree_rec, item.clone(), rank)
         }
         TreeCreation::InsertExplicit(creation_rec, item, rank) => {
             let tree_rec = create_tree(*creation_rec);
-            let new_tree = insert_explicit(&tree_rec, item.clone(), rank);
-            return new_tree;
+            insert_explicit(&tree_rec, item.clone(), rank)
+        }
+        TreeCreation::InsertHashRank(creation_rec, item) => {
+            let tree_rec: GTree<S> = create_tree(*creation_rec);
+            tree_rec.insert(item.clone())
         }
         TreeCreation::Remove(creation_rec, item) => {
             let tree_rec = create_tree(*creation_rec);
-            let new_tree = delete(&tree_rec, &item);
-            return new_tree;
+            delete(&tree_rec, &item)
         }
         TreeCreation::RemoveExplicit(creation_rec, item) => {
             let tree_rec = create_tree(*creation_rec);
-            let new_tree = delete_explicit(&tree_rec, &item);
-            return new_tree;
+            delete_explicit(&tree_rec, &item)
         }
-    }
+    };
+
+    validate::validate(&new_tree).unwrap();
+    return new_tree;
 }
 
 pub fn create_ctrl_tree<Item: Clone + Ord>(creation: TreeCreation<Item>) -> BTreeSet<Item> {
@@ -862,6 +1471,11 @@ pub fn create_ctrl_tree<Item: Clone + Ord>(creation: TreeCreation<Item>) -> BTre
             tree_rec.insert(item);
             return tree_rec;
         }
+        TreeCreation::InsertHashRank(creation_rec, item) => {
+            let mut tree_rec = create_ctrl_tree(*creation_rec);
+            tree_rec.insert(item);
+            return tree_rec;
+        }
         TreeCreation::Remove(creation_rec, item)
         | TreeCreation::RemoveExplicit(creation_rec, item) => {
             let mut tree_rec = create_ctrl_tree(*creation_rec);
@@ -0,0 +1,346 @@
+// This file implements key-value maps on top of G-trees.
+//
+// A map is simply a G-tree whose items are `KeyValue` pairs that are ordered by their keys alone. The keys determine the position of every item in the tree, the values are carried along. A `KeyValue` can be borrowed as its key, so lookups search the tree for a bare key via `NonemptySet::search_ref`, and any inner set can be used for maps.
+
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+
+use arbitrary::Arbitrary;
+
+use crate::augment::Summary;
+use crate::validate::validate;
+use crate::{delete, get, insert, replace, GTree, NonemptySet, NonemptySetMeta};
+
+/// An item of a map. Comparisons consider only the `key`, the `value` is ignored.
+///
+/// In particular, the equality of G-trees of `KeyValue`s (and `GTree::content_eq`) ignores the values. Compare `GTreeMap`s to take the values into account.
+#[derive(Debug, Clone)]
+pub struct KeyValue<K, V> {
+    pub key: K,
+    pub value: V,
+}
+
+impl<K: PartialEq, V> PartialEq for KeyValue<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        return self.key == other.key;
+    }
+}
+
+impl<K: Eq, V> Eq for KeyValue<K, V> {}
+
+impl<K: Ord, V> PartialOrd for KeyValue<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl<K: Ord, V> Ord for KeyValue<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self.key.cmp(&other.key);
+    }
+}
+
+// Hashing also considers only the key, so that maps can derive the ranks of their items from the keys alone.
+impl<K: Hash, V> Hash for KeyValue<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+// Since `KeyValue`s are compared and hashed by their keys alone, they can be borrowed as their keys.
+impl<K, V> Borrow<K> for KeyValue<K, V> {
+    fn borrow(&self) -> &K {
+        return &self.key;
+    }
+}
+
+/// A map from keys to values, stored as a G-tree of `KeyValue`s.
+///
+/// Like `GTree`, maps are persistent: all operations that modify the map return a new map and leave `self` untouched.
+#[derive(Debug, Clone)]
+pub struct GTreeMap<S: NonemptySet>(pub GTree<S>);
+
+impl<K, V, S> GTreeMap<S>
+where
+    K: Ord,
+    S: NonemptySet<Item = KeyValue<K, V>>,
+{
+    pub fn new() -> Self {
+        return GTreeMap(GTree::Empty);
+    }
+
+    /// Return the key-value pair stored for `key`, if any.
+    pub fn get_key_value<'a>(&'a self, key: &K) -> Option<(&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        return get(&self.0, key).map(|kv| (&kv.key, &kv.value));
+    }
+
+    /// Return the value stored for `key`, if any.
+    pub fn get<'a>(&'a self, key: &K) -> Option<&'a V>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        return get(&self.0, key).map(|kv| &kv.value);
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        return get(&self.0, key).is_some();
+    }
+}
+
+impl<K, V, S> GTreeMap<S>
+where
+    K: Clone + Ord,
+    V: Clone,
+    S: NonemptySet<Item = KeyValue<K, V>> + Debug,
+{
+
+    /// Map `key` to `value`. If `key` is not in the map yet, it is inserted at the given `rank`. Otherwise, the old value is replaced (and returned), and the shape of the tree stays the same.
+    pub fn insert(&self, key: K, value: V, rank: u8) -> (Self, Option<V>) {
+        match self.entry(key) {
+            Entry::Occupied(entry) => {
+                let (new_map, old) = entry.insert(value);
+                return (new_map, Some(old));
+            }
+            Entry::Vacant(entry) => return (entry.insert(value, rank), None),
+        }
+    }
+
+    /// Remove `key` from the map, returning the value it was mapped to (if any).
+    pub fn remove(&self, key: &K) -> (Self, Option<V>) {
+        match self.entry(key.clone()) {
+            Entry::Occupied(entry) => {
+                let (new_map, old) = entry.remove();
+                return (new_map, Some(old));
+            }
+            Entry::Vacant(_) => return (self.clone(), None),
+        }
+    }
+
+    /// Persistent counterpart to `get_mut`: apply `f` to the value of `key`, and return the resulting map. Return None if `key` is not in the map.
+    pub fn update<F: FnOnce(&mut V)>(&self, key: &K, f: F) -> Option<Self> {
+        match self.entry(key.clone()) {
+            Entry::Occupied(entry) => return Some(entry.update(f)),
+            Entry::Vacant(_) => return None,
+        }
+    }
+
+    /// Look up `key` in preparation for inserting, replacing or removing it.
+    pub fn entry<'a>(&'a self, key: K) -> Entry<'a, S, K, V>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        match get(&self.0, &key) {
+            Some(kv) => {
+                return Entry::Occupied(OccupiedEntry {
+                    map: self,
+                    key,
+                    value: &kv.value,
+                })
+            }
+            None => return Entry::Vacant(VacantEntry { map: self, key }),
+        }
+    }
+}
+
+// Unlike the G-trees they wrap, maps are only equal if they map equal keys to equal values.
+impl<K: PartialEq, V: PartialEq, S: NonemptySet<Item = KeyValue<K, V>>> PartialEq for GTreeMap<S> {
+    fn eq(&self, other: &Self) -> bool {
+        return self.0.len() == other.0.len() && self.0.iter().zip(other.0.iter()).all(|(a, b)| a.key == b.key && a.value == b.value);
+    }
+}
+
+impl<K: Eq, V: Eq, S: NonemptySet<Item = KeyValue<K, V>>> Eq for GTreeMap<S> {}
+
+impl<K, V, S> Default for GTreeMap<S>
+where
+    K: Ord,
+    S: NonemptySet<Item = KeyValue<K, V>>,
+{
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+/// A view into a single entry of a map, obtained via `GTreeMap::entry`.
+pub enum Entry<'a, S: NonemptySet, K, V> {
+    Occupied(OccupiedEntry<'a, S, K, V>),
+    Vacant(VacantEntry<'a, S, K>),
+}
+
+/// An entry whose key is in the map.
+pub struct OccupiedEntry<'a, S: NonemptySet, K, V> {
+    map: &'a GTreeMap<S>,
+    key: K,
+    value: &'a V,
+}
+
+/// An entry whose key is not in the map.
+pub struct VacantEntry<'a, S: NonemptySet, K> {
+    map: &'a GTreeMap<S>,
+    key: K,
+}
+
+impl<'a, K, V, S> Entry<'a, S, K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+    S: NonemptySet<Item = KeyValue<K, V>> + Debug,
+{
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => return entry.key(),
+            Entry::Vacant(entry) => return entry.key(),
+        }
+    }
+
+    /// Return a map in which the key is mapped to `default` if it was not in the map before.
+    pub fn or_insert(self, default: V, rank: u8) -> GTreeMap<S> {
+        return self.or_insert_with(|| default, rank);
+    }
+
+    /// Like `or_insert`, but only computes the default value if it is needed.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F, rank: u8) -> GTreeMap<S> {
+        match self {
+            Entry::Occupied(entry) => return entry.map.clone(),
+            Entry::Vacant(entry) => return entry.insert(default(), rank),
+        }
+    }
+
+    /// Return a map in which the value of the key has been modified by `f` if the key was in the map, or in which the key is mapped to `default` otherwise.
+    pub fn and_modify_or_insert<F: FnOnce(&mut V)>(self, f: F, default: V, rank: u8) -> GTreeMap<S> {
+        match self {
+            Entry::Occupied(entry) => return entry.update(f),
+            Entry::Vacant(entry) => return entry.insert(default, rank),
+        }
+    }
+}
+
+impl<'a, K, V, S> OccupiedEntry<'a, S, K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+    S: NonemptySet<Item = KeyValue<K, V>> + Debug,
+{
+    pub fn key(&self) -> &K {
+        return &self.key;
+    }
+
+    pub fn get(&self) -> &'a V {
+        return self.value;
+    }
+
+    /// Return a map in which the key is mapped to `value` instead, together with the old value.
+    pub fn insert(self, value: V) -> (GTreeMap<S>, V) {
+        let (new_tree, _) = replace(&self.map.0, KeyValue { key: self.key, value });
+        return (GTreeMap(new_tree), self.value.clone());
+    }
+
+    /// Return a map in which the value of the key has been modified by `f`.
+    pub fn update<F: FnOnce(&mut V)>(self, f: F) -> GTreeMap<S> {
+        let mut value = self.value.clone();
+        f(&mut value);
+        return self.insert(value).0;
+    }
+
+    /// Return a map without the key, together with the value it was mapped to.
+    pub fn remove(self) -> (GTreeMap<S>, V) {
+        let new_tree = delete(&self.map.0, &KeyValue { key: self.key, value: self.value.clone() });
+        return (GTreeMap(new_tree), self.value.clone());
+    }
+}
+
+impl<'a, K, V, S> VacantEntry<'a, S, K>
+where
+    K: Clone + Ord,
+    V: Clone,
+    S: NonemptySet<Item = KeyValue<K, V>> + Debug,
+{
+    pub fn key(&self) -> &K {
+        return &self.key;
+    }
+
+    /// Return a map in which the key is mapped to `value`, inserted at the given `rank`.
+    pub fn insert(self, value: V, rank: u8) -> GTreeMap<S> {
+        return GTreeMap(insert(&self.map.0, KeyValue { key: self.key, value }, rank));
+    }
+}
+
+#[derive(Debug, Arbitrary, Clone)]
+pub enum MapCreation<K, V> {
+    Empty,
+    Insert(Box<Self>, K, V, u8),
+    Remove(Box<Self>, K),
+    Update(Box<Self>, K, V),
+    OrInsert(Box<Self>, K, V, u8),
+}
+
+// Create a map according to a MapCreation value, validating the underlying tree after each operation. `Update` replaces the value of a key (if present) via `GTreeMap::update`.
+pub fn create_map<K: Clone + Ord + Debug, V: Clone + Debug, S: NonemptySetMeta<Item = KeyValue<K, V>>>(
+    creation: MapCreation<K, V>,
+) -> GTreeMap<S>
+where
+    Summary<S>: PartialEq,
+{
+    let new_map = match creation {
+        MapCreation::Empty => GTreeMap::new(),
+        MapCreation::Insert(creation_rec, key, value, rank) => {
+            let map_rec: GTreeMap<S> = create_map(*creation_rec);
+            map_rec.insert(key, value, rank).0
+        }
+        MapCreation::Remove(creation_rec, key) => {
+            let map_rec: GTreeMap<S> = create_map(*creation_rec);
+            map_rec.remove(&key).0
+        }
+        MapCreation::Update(creation_rec, key, value) => {
+            let map_rec: GTreeMap<S> = create_map(*creation_rec);
+            match map_rec.update(&key, |v| *v = value) {
+                None => map_rec,
+                Some(new_map) => new_map,
+            }
+        }
+        MapCreation::OrInsert(creation_rec, key, value, rank) => {
+            let map_rec: GTreeMap<S> = create_map(*creation_rec);
+            map_rec.entry(key).or_insert(value, rank)
+        }
+    };
+
+    validate(&new_map.0).unwrap();
+    return new_map;
+}
+
+pub fn create_ctrl_map<K: Clone + Ord, V: Clone>(creation: MapCreation<K, V>) -> BTreeMap<K, V> {
+    match creation {
+        MapCreation::Empty => return BTreeMap::new(),
+        MapCreation::Insert(creation_rec, key, value, _rank) => {
+            let mut map_rec = create_ctrl_map(*creation_rec);
+            map_rec.insert(key, value);
+            return map_rec;
+        }
+        MapCreation::Remove(creation_rec, key) => {
+            let mut map_rec = create_ctrl_map(*creation_rec);
+            map_rec.remove(&key);
+            return map_rec;
+        }
+        MapCreation::Update(creation_rec, key, value) => {
+            let mut map_rec = create_ctrl_map(*creation_rec);
+            if let Some(v) = map_rec.get_mut(&key) {
+                *v = value;
+            }
+            return map_rec;
+        }
+        MapCreation::OrInsert(creation_rec, key, value, _rank) => {
+            let mut map_rec = create_ctrl_map(*creation_rec);
+            map_rec.entry(key).or_insert(value);
+            return map_rec;
+        }
+    }
+}
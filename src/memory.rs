@@ -0,0 +1,70 @@
+// This file measures how many bytes a G-tree occupies.
+//
+// `item_slot_count` tells how many slots the inner sets reserve per item, but not how large a slot is, nor what the G-nodes themselves cost. `memory_report` counts bytes instead: every G-node is a shared allocation holding the reference counts and the `GTreeNode` (including whatever part of its inner set is stored inline), and every inner set reports the bytes it has allocated on top of that via `NonemptySetMeta::heap_bytes`. The report does not include the allocator's own bookkeeping, nor (unless requested via `memory_report_with_items`) any heap memory owned by the items themselves, such as the buffers of `Vec<u8>` items.
+
+use std::{fmt::Debug, mem::size_of};
+
+use crate::{GTree, GTreeNode, NonemptySetMeta};
+
+/// The number of bytes occupied by a G-tree, see `memory_report`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryReport {
+    pub item_count: usize,
+    pub gnode_count: usize,
+    /// The bytes of the allocations of all G-nodes.
+    pub gnode_bytes: usize,
+    /// The bytes that the inner sets have allocated in addition to their G-nodes.
+    pub set_heap_bytes: usize,
+    /// The bytes that the items have allocated, if measured by `memory_report_with_items`.
+    pub item_heap_bytes: usize,
+}
+
+impl MemoryReport {
+    pub fn total_bytes(&self) -> usize {
+        return self.gnode_bytes + self.set_heap_bytes + self.item_heap_bytes;
+    }
+
+    pub fn bytes_per_item(&self) -> f64 {
+        return (self.total_bytes() as f64) / (self.item_count as f64);
+    }
+}
+
+/// The number of bytes of an `Rc` or `Arc` allocation of a `T`: the value and the strong and weak reference counts.
+pub fn shared_allocation_bytes<T>() -> usize {
+    return size_of::<T>() + 2 * size_of::<usize>();
+}
+
+/// Count the bytes occupied by a G-tree.
+pub fn memory_report<S: NonemptySetMeta>(t: &GTree<S>) -> MemoryReport {
+    let mut report = MemoryReport {
+        item_count: t.len(),
+        ..MemoryReport::default()
+    };
+
+    let mut stack = vec![t];
+    while let Some(t) = stack.pop() {
+        if let GTree::NonEmpty(node) = t {
+            report.gnode_count += 1;
+            report.gnode_bytes += shared_allocation_bytes::<GTreeNode<S>>();
+            report.set_heap_bytes += node.set.heap_bytes();
+
+            stack.push(&node.right);
+            // Not `pairs_descending`, which would make packed sets decode their items.
+            stack.extend(node.set.left_subtrees_descending());
+        }
+    }
+
+    return report;
+}
+
+/// Count the bytes occupied by a G-tree, including the heap memory owned by its items, as given by `item_heap_bytes`.
+///
+/// This is only meaningful for inner sets that store their items as they are. Sets that encode their items, such as prefix sets, already count the encoded items in `NonemptySetMeta::heap_bytes`.
+pub fn memory_report_with_items<S: NonemptySetMeta, F: Fn(&S::Item) -> usize>(t: &GTree<S>, item_heap_bytes: F) -> MemoryReport
+where
+    S::Item: Clone + Ord + Debug,
+{
+    let mut report = memory_report(t);
+    report.item_heap_bytes = t.iter().map(item_heap_bytes).sum();
+    return report;
+}
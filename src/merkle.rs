@@ -0,0 +1,183 @@
+// This file implements Merkle hashing of G-trees.
+//
+// G-trees are history independent: two trees with the same items at the same ranks have the same shape, no matter in which order the items were inserted or removed. Hashing each G-node over its rank, its items, the hashes of their left subtrees, and the hash of its right subtree thus yields a root hash that depends on the contents of the tree alone, so that replicas can compare their trees by root hash, and subtrees can be addressed by their hashes.
+//
+// A `MerkleHasher` supplies the hash function. Every G-node caches its hash, which `GTreeNode::new` and `GTreeNode::refresh` compute from the cached hashes of the subtrees, like the size and the summary. All operations that build or modify G-nodes thus maintain the hashes. The trivial hasher `()` does not hash at all, and it does not even look at the items.
+//
+// A `Proof` lets a client that only knows the root hash check whether a key is in the tree. It consists of the G-nodes that `has` visits: their ranks, items, left subtree hashes and right subtree hashes. The client recomputes the hash of each G-node, checks that it matches the hash that the previous G-node (or the root hash) gives for the subtree, and repeats the search of `has` within the G-nodes. The search ends at the item that equals the key, proving membership, or at an empty subtree between the neighbouring items of the key, proving absence.
+
+use std::borrow::Borrow;
+use std::fmt::Debug;
+
+use crate::{GTree, NonemptySet};
+
+/// A hash function for G-nodes.
+pub trait MerkleHasher<Item>: Clone + Debug {
+    type Digest: Clone + Eq + Debug;
+
+    /// Hash a G-node, given its rank, its items in descending order together with the digests of their left subtrees (None for empty subtrees), and the digest of its right subtree (None if it is empty).
+    fn hash_node<'a, Pairs: Iterator<Item = (&'a Item, Option<&'a Self::Digest>)>>(rank: u8, pairs: Pairs, right: Option<&Self::Digest>) -> Self::Digest
+    where
+        Item: 'a,
+        Self::Digest: 'a;
+}
+
+/// The trivial hasher, for trees that do not need hashes.
+impl<Item> MerkleHasher<Item> for () {
+    type Digest = ();
+
+    fn hash_node<'a, Pairs: Iterator<Item = (&'a Item, Option<&'a Self::Digest>)>>(_rank: u8, _pairs: Pairs, _right: Option<&Self::Digest>) -> Self::Digest
+    where
+        Item: 'a,
+        Self::Digest: 'a,
+    {
+    }
+}
+
+/// The digest type of the hasher of a NonemptySet.
+pub type Digest<S> = <<S as NonemptySet>::Hasher as MerkleHasher<<S as NonemptySet>::Item>>::Digest;
+
+/// Hash a G-node with the given contents, from the cached digests of its subtrees.
+pub(crate) fn hash_node<S: NonemptySet>(set: &S, right: &GTree<S>, rank: u8) -> Digest<S> {
+    // The pairs are only enumerated once the hasher asks for them, because some sets (such as packed sets) decode their items for `pairs_descending`.
+    let pairs = std::iter::once(set).flat_map(|set| set.pairs_descending()).map(|(item, left_subtree)| (item, left_subtree.root_hash_ref()));
+    return S::Hasher::hash_node(rank, pairs, right.root_hash_ref());
+}
+
+impl<S: NonemptySet> GTree<S> {
+    /// Return the hash of the root G-node, in O(1) time, or None if the tree is empty.
+    pub fn root_hash(&self) -> Option<Digest<S>> {
+        return self.root_hash_ref().cloned();
+    }
+
+    fn root_hash_ref(&self) -> Option<&Digest<S>> {
+        match self {
+            GTree::Empty => return None,
+            GTree::NonEmpty(node) => return Some(&node.hash),
+        }
+    }
+}
+
+/// A G-node on the search path of a proof.
+pub struct ProofNode<S: NonemptySet> {
+    pub rank: u8,
+    /// The items of the G-node in descending order, with the hashes of their left subtrees (None for empty subtrees).
+    pub pairs: Vec<(S::Item, Option<Digest<S>>)>,
+    /// The hash of the right subtree, or None if it is empty.
+    pub right: Option<Digest<S>>,
+}
+
+/// A proof that a key is or is not in a tree with a certain root hash.
+pub struct Proof<S: NonemptySet> {
+    /// Whether the key is in the tree.
+    pub contains: bool,
+    /// The G-nodes that `has` visits, starting at the root.
+    pub path: Vec<ProofNode<S>>,
+}
+
+impl<S: NonemptySet> Clone for ProofNode<S>
+where
+    S::Item: Clone,
+{
+    fn clone(&self) -> Self {
+        return ProofNode {
+            rank: self.rank,
+            pairs: self.pairs.clone(),
+            right: self.right.clone(),
+        };
+    }
+}
+
+impl<S: NonemptySet> Debug for ProofNode<S>
+where
+    S::Item: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return f.debug_struct("ProofNode").field("rank", &self.rank).field("pairs", &self.pairs).field("right", &self.right).finish();
+    }
+}
+
+impl<S: NonemptySet> Clone for Proof<S>
+where
+    S::Item: Clone,
+{
+    fn clone(&self) -> Self {
+        return Proof {
+            contains: self.contains,
+            path: self.path.clone(),
+        };
+    }
+}
+
+impl<S: NonemptySet> Debug for Proof<S>
+where
+    S::Item: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return f.debug_struct("Proof").field("contains", &self.contains).field("path", &self.path).finish();
+    }
+}
+
+/// Prove whether `key` is in the tree, see `verify`.
+pub fn prove<S: NonemptySet, Q: Ord + ?Sized>(t: &GTree<S>, key: &Q) -> Proof<S>
+where
+    S::Item: Borrow<Q> + Clone,
+{
+    let mut path = vec![];
+    let mut t = t;
+    loop {
+        match t {
+            GTree::Empty => {
+                return Proof { contains: false, path };
+            }
+            GTree::NonEmpty(node) => {
+                path.push(ProofNode {
+                    rank: node.rank,
+                    pairs: node.set.pairs_descending().map(|(item, left_subtree)| (item.clone(), left_subtree.root_hash())).collect(),
+                    right: node.right.root_hash(),
+                });
+                match node.set.search_left_subtree(key) {
+                    None => t = &node.right,
+                    Some((_, true)) => return Proof { contains: true, path },
+                    Some((left_subtree, false)) => t = left_subtree,
+                }
+            }
+        }
+    }
+}
+
+/// Check a proof against the root hash of a tree (None for the empty tree). Return true if the proof shows that `key` is in the tree (if `proof.contains`) or that it is not (otherwise), and false if the proof is invalid.
+pub fn verify<S: NonemptySet, Q: Ord + ?Sized>(root_hash: Option<&Digest<S>>, key: &Q, proof: &Proof<S>) -> bool
+where
+    S::Item: Borrow<Q>,
+{
+    // The hash that the next G-node of the path must have, or None if the search has reached an empty subtree.
+    let mut expected = root_hash;
+    for (i, node) in proof.path.iter().enumerate() {
+        let hash = match expected {
+            None => return false, // The path is longer than the search.
+            Some(hash) => hash,
+        };
+
+        if S::Hasher::hash_node(node.rank, node.pairs.iter().map(|(item, left_hash)| (item, left_hash.as_ref())), node.right.as_ref()) != *hash {
+            return false;
+        }
+
+        // Find the least item that is greater than or equal to the key, as `search_left_subtree` does.
+        let mut next = node.right.as_ref();
+        for (item, left_hash) in node.pairs.iter() {
+            match item.borrow().cmp(key) {
+                std::cmp::Ordering::Less => break,
+                std::cmp::Ordering::Equal => {
+                    // The key is in the tree, so the search ends here.
+                    return proof.contains && i + 1 == proof.path.len();
+                }
+                std::cmp::Ordering::Greater => next = left_hash.as_ref(),
+            }
+        }
+        expected = next;
+    }
+
+    // The path must end at an empty subtree, between the neighbouring items of the key.
+    return !proof.contains && expected.is_none();
+}
@@ -0,0 +1,93 @@
+// This file implements in-place insertion and deletion for G-trees.
+//
+// The persistent `insert` and `delete` copy every G-node on the path to the modified part of the tree, even if nobody else holds a reference to the old tree. The mutable variants walk down the same path, but obtain each G-node via `PointerFamily::make_mut`, which only clones G-nodes (and k-list vertices) that are shared. The descent stops at the G-node where the tree actually changes shape, that is, the G-node that contains the item or into which the item is inserted. The subtree rooted there is replaced with the result of the persistent function, which yields exactly the tree the persistent function would produce for the whole tree, because the shape of a G-tree is determined by its items and their ranks. The subtree is handed over by value, so that the persistent function can consume the G-nodes that are not shared (see `unzip_find_owned` and `zip2_owned`), and these in turn use the consuming methods of their inner sets.
+//
+// Removing an item that the tree does not contain leaves the tree unchanged. `remove_mut` checks for this first, because walking down the path would copy all shared G-nodes on it for nothing. (Inserting an item that the tree contains already is not necessarily a no-op, since it moves the item to its new rank.)
+
+use std::fmt::Debug;
+
+use crate::pointer::PointerFamily;
+use crate::{has, unzip_find_owned, zip2_owned, zip3_owned, GTree, NonemptySet};
+
+impl<S: NonemptySet + Debug> GTree<S>
+where
+    S::Item: Ord,
+{
+    /// Insert `item` at the given rank, like `insert`, but modify the tree in place where possible. Return whether the item was new to the tree.
+    pub fn insert_mut(&mut self, item: S::Item, rank: u8) -> bool {
+        let old_len = self.len();
+        insert_mut_(self, item, rank);
+        return self.len() > old_len;
+    }
+
+    /// Remove `item`, like `delete`, but modify the tree in place where possible. Return whether the tree contained the item.
+    pub fn remove_mut(&mut self, item: &S::Item) -> bool {
+        if !has(self, item) {
+            return false;
+        }
+        remove_mut_(self, item);
+        return true;
+    }
+}
+
+fn insert_mut_<S: NonemptySet + Debug>(t: &mut GTree<S>, item: S::Item, rank: u8)
+where
+    S::Item: Ord,
+{
+    if let GTree::NonEmpty(node) = t {
+        if rank < node.rank {
+            // The item belongs into a subtree of this G-node, so unless this G-node already contains the item (at a different rank), only that subtree changes.
+            // We keep only the item: holding on to a clone of its left subtree would make `make_mut` copy the G-node below, and keep `unzip_find_owned` from taking it apart.
+            match node.set.search(&item).map(|(greater, _)| greater) {
+                None => {
+                    let node = S::Pointers::make_mut(node);
+                    insert_mut_(&mut node.right, item, rank);
+                    node.refresh();
+                    return;
+                }
+                Some(greater) => {
+                    if greater != item {
+                        let node = S::Pointers::make_mut(node);
+                        node.set.update_left_subtree(&greater, |left_subtree| insert_mut_(left_subtree, item, rank));
+                        node.refresh();
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    // Like `insert`, but consuming the subtree.
+    let (left, _, right) = unzip_find_owned(std::mem::replace(t, GTree::Empty), &item);
+    *t = zip3_owned(left, item, rank, right);
+}
+
+fn remove_mut_<S: NonemptySet + Debug>(t: &mut GTree<S>, item: &S::Item)
+where
+    S::Item: Ord,
+{
+    match t {
+        GTree::Empty => return,
+        // As in `insert_mut_`, we keep only the item.
+        GTree::NonEmpty(node) => match node.set.search(item).map(|(greater, _)| greater) {
+            None => {
+                let node = S::Pointers::make_mut(node);
+                remove_mut_(&mut node.right, item);
+                node.refresh();
+                return;
+            }
+            Some(greater) => {
+                if &greater != item {
+                    let node = S::Pointers::make_mut(node);
+                    node.set.update_left_subtree(&greater, |left_subtree| remove_mut_(left_subtree, item));
+                    node.refresh();
+                    return;
+                }
+            }
+        },
+    }
+
+    // This G-node contains the item. Like `delete`, but consuming the subtree.
+    let (left, _, right) = unzip_find_owned(std::mem::replace(t, GTree::Empty), item);
+    *t = zip2_owned(left, right);
+}
@@ -0,0 +1,396 @@
+// This file implements an inner set that is itself a G-tree.
+//
+// The size of G-nodes follows a geometric distribution, so some G-nodes are much larger than K. Inner sets that are lists or arrays need time linear in the size of a G-node for `split` and `join`. The nested set stores the item-left_subtree pairs of a G-node in a G-tree of k-lists instead, so `split` and `join` become `unzip` and `zip2` of the inner G-tree, and `search`, `insert_min` and `remove_min` are logarithmic in the size of the G-node as well.
+//
+// The inner G-tree needs ranks for its pairs. We derive them from the hash of the item, just like the ranks of the outer tree, but salted, because all items of a G-node have the same outer rank, so unsalted inner ranks would be correlated with the outer ranks. Since the inner ranks are a function of the items, the shape of the inner G-tree is a function of the items of the G-node.
+//
+// The augmentation of the inner G-tree caches, for every inner subtree, the number of items and the outer summary of its pairs and their left subtrees. This keeps `NonemptySet::total_len`, `summary` and `fold_pairs` logarithmic as well, which matters because `GTreeNode::new` calls the first two for every G-node it creates.
+
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::Bound;
+
+use crate::augment::{summarize, Augmentation, Monoid, Summary};
+use crate::klist::NonemptyReverseKList;
+use crate::memory::memory_report;
+use crate::pointer::{PointerFamily, RcFamily};
+use crate::rank::{rank_from_hash, StableHasher};
+use crate::{unzip_find, unzip_find_owned, zip2, zip2_owned, zip3, GTree, NonemptySet, NonemptySetMeta, Set};
+
+/// An item of the outer tree together with its left subtree, stored as an item of the inner G-tree of a `NonemptyNestedSet`. Comparisons consider only the item.
+pub struct Pair<S: NonemptySet>(pub (S::Item, GTree<S>));
+
+impl<S: NonemptySet> Clone for Pair<S>
+where
+    S::Item: Clone,
+{
+    fn clone(&self) -> Self {
+        return Pair(self.0.clone());
+    }
+}
+
+impl<S: NonemptySet + Debug> Debug for Pair<S>
+where
+    S::Item: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return f.debug_tuple("Pair").field(&self.0).finish();
+    }
+}
+
+impl<S: NonemptySet> PartialEq for Pair<S>
+where
+    S::Item: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        return self.0 .0 == other.0 .0;
+    }
+}
+
+impl<S: NonemptySet> Eq for Pair<S> where S::Item: Eq {}
+
+impl<S: NonemptySet> PartialOrd for Pair<S>
+where
+    S::Item: Ord,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl<S: NonemptySet> Ord for Pair<S>
+where
+    S::Item: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self.0 .0.cmp(&other.0 .0);
+    }
+}
+
+/// The summary of some pairs of a `NonemptyNestedSet`: the number of items in the pairs and their left subtrees, and the combined (outer) summary of those items.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairSummary<M> {
+    pub len: usize,
+    pub summary: M,
+}
+
+impl<M: Monoid> Monoid for PairSummary<M> {
+    fn neutral() -> Self {
+        return PairSummary {
+            len: 0,
+            summary: M::neutral(),
+        };
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        return PairSummary {
+            len: self.len + other.len,
+            summary: self.summary.combine(&other.summary),
+        };
+    }
+}
+
+/// The augmentation of the inner G-tree of a `NonemptyNestedSet` over `S`.
+pub struct PairAugmentation<S>(PhantomData<S>);
+
+impl<S> Clone for PairAugmentation<S> {
+    fn clone(&self) -> Self {
+        return PairAugmentation(PhantomData);
+    }
+}
+
+impl<S> Debug for PairAugmentation<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return f.write_str("PairAugmentation");
+    }
+}
+
+impl<S: NonemptySet> Augmentation<Pair<S>> for PairAugmentation<S> {
+    type Summary = PairSummary<Summary<S>>;
+
+    fn summarize(pair: &Pair<S>) -> Self::Summary {
+        let (item, left_subtree) = &pair.0;
+        return PairSummary {
+            len: 1 + left_subtree.len(),
+            summary: left_subtree.summary().combine(&summarize::<S>(item)),
+        };
+    }
+}
+
+/// The inner set type of the inner G-tree of a `NonemptyNestedSet`.
+pub type InnerKList<const K: usize, const L: usize, I, A, P> =
+    NonemptyReverseKList<L, Pair<NonemptyNestedSet<K, L, I, A, P>>, PairAugmentation<NonemptyNestedSet<K, L, I, A, P>>, P>;
+
+/// A non-empty set of item-left_subtree pairs, stored in a G-tree of k-lists with target G-node size L. The outer G-trees target G-nodes of size K.
+pub struct NonemptyNestedSet<const K: usize, const L: usize, I: Clone + Ord + Debug + Hash, A: Augmentation<I> = (), P: PointerFamily = RcFamily> {
+    pairs: GTree<InnerKList<K, L, I, A, P>>,
+}
+
+// Distinguishes the hashes for inner ranks from those for outer ranks.
+const INNER_RANK_SALT: u64 = 0x6e65_7374_6564;
+
+impl<const K: usize, const L: usize, I: Clone + Ord + Debug + Hash, A: Augmentation<I>, P: PointerFamily> NonemptyNestedSet<K, L, I, A, P> {
+    // Internal helper function: wrap a possibly empty inner tree.
+    fn new_set(pairs: GTree<InnerKList<K, L, I, A, P>>) -> Set<Self> {
+        if pairs.is_empty() {
+            return Set::Empty;
+        } else {
+            return Set::NonEmpty(NonemptyNestedSet { pairs });
+        }
+    }
+
+    // Internal helper function: the rank of a pair in the inner tree.
+    fn inner_rank(item: &I) -> u8 {
+        let mut hasher = StableHasher::new();
+        INNER_RANK_SALT.hash(&mut hasher);
+        item.hash(&mut hasher);
+        return rank_from_hash(hasher.finish(), L);
+    }
+
+    // Internal helper function: a pair to compare against, for searching the inner tree for `key`.
+    fn probe(key: &I) -> Pair<Self> {
+        return Pair((key.clone(), GTree::Empty));
+    }
+
+    fn probe_bound(bound: Bound<&I>) -> Bound<Pair<Self>> {
+        return bound.map(Self::probe);
+    }
+}
+
+impl<const K: usize, const L: usize, I: Clone + Ord + Debug + Hash, A: Augmentation<I>, P: PointerFamily> Clone for NonemptyNestedSet<K, L, I, A, P> {
+    fn clone(&self) -> Self {
+        return NonemptyNestedSet { pairs: self.pairs.clone() };
+    }
+}
+
+impl<const K: usize, const L: usize, I: Clone + Ord + Debug + Hash, A: Augmentation<I>, P: PointerFamily> Debug for NonemptyNestedSet<K, L, I, A, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return f.debug_struct("NonemptyNestedSet").field("pairs", &self.pairs).finish();
+    }
+}
+
+// The shape of the inner tree is determined by the items, so comparing the pairs in order suffices.
+impl<const K: usize, const L: usize, I: Clone + Ord + Debug + Hash, A: Augmentation<I>, P: PointerFamily> PartialEq for NonemptyNestedSet<K, L, I, A, P> {
+    fn eq(&self, other: &Self) -> bool {
+        return self.pairs.len() == other.pairs.len() && self.pairs_descending().eq(other.pairs_descending());
+    }
+}
+
+impl<const K: usize, const L: usize, I: Clone + Ord + Debug + Hash, A: Augmentation<I>, P: PointerFamily> Eq for NonemptyNestedSet<K, L, I, A, P> {}
+
+impl<const K: usize, const L: usize, I: Clone + Ord + Debug + Hash, A: Augmentation<I>, P: PointerFamily> Hash for NonemptyNestedSet<K, L, I, A, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for pair in self.pairs_descending() {
+            pair.hash(state);
+        }
+    }
+}
+
+impl<const K: usize, const L: usize, I: Clone + Ord + Debug + Hash, A: Augmentation<I>, P: PointerFamily> NonemptySet for NonemptyNestedSet<K, L, I, A, P> {
+    type Item = I;
+    type Pointers = P;
+    type Augmentation = A;
+    type Hasher = ();
+
+    const TARGET_NODE_SIZE: usize = K;
+
+    fn singleton(item: (Self::Item, GTree<Self>)) -> Self {
+        let rank = Self::inner_rank(&item.0);
+        return NonemptyNestedSet {
+            pairs: zip3(&GTree::Empty, Pair(item), rank, &GTree::Empty),
+        };
+    }
+
+    fn insert_min(&self, new_min: (Self::Item, GTree<Self>)) -> Self {
+        let rank = Self::inner_rank(&new_min.0);
+        return NonemptyNestedSet {
+            pairs: zip3(&GTree::Empty, Pair(new_min), rank, &self.pairs),
+        };
+    }
+
+    fn remove_min(&self) -> ((Self::Item, GTree<Self>), Set<Self>) {
+        let min = self.pairs.iter().next().unwrap(/* the set is never empty */);
+        let (_, found, greater) = unzip_find(&self.pairs, min);
+        let (Pair(min), _) = found.unwrap(/* the tree contains its least item */);
+        return (min, Self::new_set(greater));
+    }
+
+    fn split(&self, key: &Self::Item) -> (Set<Self>, Option<GTree<Self>> /* left subtree of key (if key is in self, else None) */, Set<Self>) {
+        let (less, found, greater) = unzip_find(&self.pairs, &Self::probe(key));
+        return (
+            Self::new_set(less),
+            found.map(|(Pair((_, left_subtree)), _)| left_subtree),
+            Self::new_set(greater),
+        );
+    }
+
+    fn join(left: &Self, right: &Self) -> Self {
+        return NonemptyNestedSet {
+            pairs: zip2(&left.pairs, &right.pairs),
+        };
+    }
+
+    fn split_owned(self, key: &Self::Item) -> (Set<Self>, Option<(Self::Item, GTree<Self>)>, Set<Self>) {
+        let (less, found, greater) = unzip_find_owned(self.pairs, &Self::probe(key));
+        return (Self::new_set(less), found.map(|(Pair(pair), _)| pair), Self::new_set(greater));
+    }
+
+    fn join_owned(left: Self, right: Self) -> Self {
+        return NonemptyNestedSet {
+            pairs: zip2_owned(left.pairs, right.pairs),
+        };
+    }
+
+    fn remove_min_owned(self) -> ((Self::Item, GTree<Self>), Set<Self>) {
+        let min = self.pairs.iter().next().unwrap(/* the set is never empty */).clone();
+        let (_, found, greater) = unzip_find_owned(self.pairs, &min);
+        let (Pair(min), _) = found.unwrap(/* the tree contains its least item */);
+        return (min, Self::new_set(greater));
+    }
+
+    fn search(&self, key: &Self::Item) -> Option<(Self::Item, GTree<Self>)> {
+        return self.pairs.first_in_range(Self::probe(key)..).map(|pair| pair.0.clone());
+    }
+
+    fn search_ref<Q: Ord + ?Sized>(&self, key: &Q) -> Option<(&Self::Item, &GTree<Self>)>
+    where
+        Self::Item: Borrow<Q>,
+    {
+        // Descend through the inner G-tree like `has` does, remembering the least pair greater than the key seen so far.
+        let mut candidate = None;
+        let mut t = &self.pairs;
+        while let GTree::NonEmpty(node) = t {
+            match node.set.search_ref_by(&|pair: &Pair<Self>| key.cmp(pair.0.0.borrow())) {
+                None => t = &node.right,
+                Some((Pair((item, left_subtree)), inner_left_subtree)) => {
+                    if item.borrow() == key {
+                        return Some((item, left_subtree));
+                    }
+                    candidate = Some((item, left_subtree));
+                    t = inner_left_subtree;
+                }
+            }
+        }
+        return candidate;
+    }
+
+    fn pairs_descending(&self) -> impl Iterator<Item = (&Self::Item, &GTree<Self>)> {
+        return self.pairs.iter().rev().map(|Pair((item, left_subtree))| (item, left_subtree));
+    }
+
+    fn pairs_descending_from<Q: Ord + ?Sized>(&self, key: &Q) -> impl Iterator<Item = (&Self::Item, &GTree<Self>)>
+    where
+        Self::Item: Borrow<Q>,
+    {
+        let upper = match self.search_ref(key) {
+            None => Bound::Unbounded,
+            Some((item, _)) => Bound::Included(Self::probe(item)),
+        };
+        return self.pairs.range((Bound::Unbounded, upper)).rev().map(|Pair((item, left_subtree))| (item, left_subtree));
+    }
+
+    fn from_descending_pairs(pairs: Vec<(Self::Item, GTree<Self>)>) -> Self {
+        assert!(!pairs.is_empty(), "Cannot create a set from zero pairs.");
+        return NonemptyNestedSet {
+            pairs: GTree::from_sorted_ranked(pairs.into_iter().rev().map(|pair| {
+                let rank = Self::inner_rank(&pair.0);
+                return (Pair(pair), rank);
+            })),
+        };
+    }
+
+    fn total_len(&self) -> usize {
+        return self.pairs.summary().len;
+    }
+
+    fn summary(&self) -> Summary<Self> {
+        return self.pairs.summary().summary;
+    }
+
+    fn fold_pairs(&self, lower: Bound<&I>, upper: Bound<&I>) -> Summary<Self> {
+        return self
+            .pairs
+            .fold_range((Self::probe_bound(lower), Self::probe_bound(upper)))
+            .summary;
+    }
+}
+
+impl<const K: usize, const L: usize, I: Clone + Ord + Debug + Hash, A: Augmentation<I>, P: PointerFamily> NonemptySetMeta for NonemptyNestedSet<K, L, I, A, P> {
+    /// Return a reference to the maximal item in the set.
+    fn get_max(&self) -> &Self::Item {
+        return &self.pairs.iter().next_back().unwrap(/* the set is never empty */).0 .0;
+    }
+
+    /// Return a reference to the minimal item in the set.
+    fn get_min(&self) -> &Self::Item {
+        return &self.pairs.iter().next().unwrap(/* the set is never empty */).0 .0;
+    }
+
+    fn len(&self) -> usize {
+        return self.pairs.len();
+    }
+
+    fn get_pair_by_index(&self, index: usize) -> Option<(&Self::Item, &GTree<Self>)> {
+        return self.pairs.select(index).map(|Pair((item, left_subtree))| (item, left_subtree));
+    }
+
+    fn from_descending(items: &[Self::Item]) -> Self {
+        return Self::from_descending_pairs(items.iter().map(|item| (item.clone(), GTree::Empty)).collect());
+    }
+
+    fn heap_bytes(&self) -> usize {
+        return memory_report(&self.pairs).total_bytes();
+    }
+
+    // The slots are those of the k-lists of the inner tree.
+    fn item_slot_count(&self) -> usize {
+        let mut ret = 0;
+        let mut stack = vec![&self.pairs];
+        while let Some(t) = stack.pop() {
+            if let GTree::NonEmpty(node) = t {
+                ret += node.set.item_slot_count();
+                stack.push(&node.right);
+                for (_, left_subtree) in node.set.pairs_descending() {
+                    stack.push(left_subtree);
+                }
+            }
+        }
+        return ret;
+    }
+}
+
+/// Return the physical height of a G-tree over nested sets, that is, the greatest number of allocations that a search has to visit: the G-nodes of the outer tree, and the k-list vertices of their inner trees.
+pub fn physical_height<const K: usize, const L: usize, I: Clone + Ord + Debug + Hash, A: Augmentation<I>, P: PointerFamily>(
+    t: &GTree<NonemptyNestedSet<K, L, I, A, P>>,
+) -> usize {
+    match t {
+        GTree::Empty => return 0,
+        GTree::NonEmpty(node) => {
+            // The G-node itself holds the right subtree, and a pointer to the root of the inner tree.
+            let mut height = 1 + physical_height(&node.right);
+            inner_physical_height(&node.set.pairs, 1, &mut height);
+            return height;
+        }
+    }
+}
+
+// Internal helper function: raise `height` to the physical height through every pair of an inner tree, whose root is reached after visiting `depth` allocations. As in `klist::physical_height`, the right subtree of an inner G-node is reached from its final vertex.
+fn inner_physical_height<const K: usize, const L: usize, I: Clone + Ord + Debug + Hash, A: Augmentation<I>, P: PointerFamily>(
+    t: &GTree<InnerKList<K, L, I, A, P>>,
+    depth: usize,
+    height: &mut usize,
+) {
+    if let GTree::NonEmpty(node) = t {
+        let len = node.set.len();
+        for i in 0..len {
+            let (Pair((_, left_subtree)), inner_left_subtree) = node.set.get_pair_by_index(i).unwrap(/* i < len */);
+            let vertex_depth = depth + 1 + i / L;
+            *height = std::cmp::max(*height, vertex_depth + physical_height(left_subtree));
+            inner_physical_height(inner_left_subtree, vertex_depth, height);
+        }
+        inner_physical_height(&node.right, depth + 1 + (len - 1) / L, height);
+    }
+}
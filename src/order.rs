@@ -0,0 +1,83 @@
+// This file implements order statistics on G-trees, based on the subtree sizes stored in every G-node.
+//
+// Both queries descend along a single path from the root, so they visit O(height) G-nodes, and at each G-node they walk its pairs from the greatest item down, looking at the sizes of their left subtrees.
+
+use crate::{GTree, NonemptySet};
+
+impl<S: NonemptySet> GTree<S>
+where
+    S::Item: Ord,
+{
+    /// Return the number of items in the tree that are strictly less than `key`.
+    pub fn count_less_than(&self, key: &S::Item) -> usize {
+        let mut count = 0;
+        let mut t = self;
+
+        loop {
+            match t {
+                GTree::Empty => return count,
+                GTree::NonEmpty(node) => {
+                    // We count the items that are not less than the key, down to the key or to the first item less than the key.
+                    // We then continue in the left subtree of the least item that is greater than the key, or in the right subtree if there is no such item. All other items that we did not count are less than the key.
+                    let mut not_less = 0; // number of items not less than the key, other than those in `next`
+                    let mut next = &node.right;
+                    for (item, left_subtree) in node.set.pairs_descending_lent() {
+                        if *item < *key {
+                            break;
+                        }
+                        // `next` and `item` hold only items that are not less than the key.
+                        not_less += next.len() + 1;
+                        if *item == *key {
+                            return count + node.size - not_less;
+                        }
+                        next = left_subtree;
+                    }
+
+                    count += node.size - not_less - next.len();
+                    t = next;
+                }
+            }
+        }
+    }
+}
+
+impl<S: NonemptySet> GTree<S> {
+    /// Return the item at position `index` in ascending order (the least item is at index 0), or None if `index` is not less than the number of items.
+    pub fn select(&self, index: usize) -> Option<&S::Item> {
+        let mut remaining = index;
+        let mut t = self;
+
+        loop {
+            match t {
+                GTree::Empty => return None,
+                GTree::NonEmpty(node) => {
+                    if remaining >= node.size {
+                        return None;
+                    }
+
+                    // The items above the current pair are those of the right subtree and of the pairs we have already passed.
+                    let mut below = node.size - node.right.len(); // number of items less than all items of `next`
+                    let mut next = &node.right;
+                    if remaining < below {
+                        let mut above = node.right.len();
+                        for (item, left_subtree) in node.set.pairs_descending() {
+                            let item_index = node.size - above - 1;
+                            if remaining == item_index {
+                                return Some(item);
+                            }
+                            below = item_index - left_subtree.len();
+                            if remaining >= below {
+                                next = left_subtree;
+                                break;
+                            }
+                            above += 1 + left_subtree.len();
+                        }
+                    }
+
+                    remaining -= below;
+                    t = next;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,356 @@
+// This file implements an inner set for integer items that stores its items bit-packed.
+//
+// Every slot of a k-list is an `Option<(I, GTree<Self>)>`, which for u32 items takes 24 bytes: 4 for the item, 8 for the pointer to the left subtree, and the rest for padding and the tag of the option. The packed set stores the items and the left subtrees of a G-node in two parallel buffers instead. The items are encoded relative to a frame of reference: the least item of the set is stored once, and every item is stored as its difference to it, using only as many bits as the greatest difference requires. Most items live in G-nodes of low rank, whose items all lie between two neighbouring items of higher rank, so in a large tree the differences are much narrower than the items. Like the array set, the packed set stores its pairs in descending order, and K only determines the rank distribution.
+//
+// The difficulty is that `NonemptySet::pairs_descending` (and with it `GTree::iter` and `GTree::get`), `NonemptySetMeta::get_min` and friends lend out references to items, which bit-packed items cannot provide. Only for those methods does the packed set decode its items into a cache, which it keeps until the set is dropped. Everything that merely looks at the items, such as computing sizes, summaries, statistics and set algebra, goes through `NonemptySet::pairs_descending_lent` instead, which the packed set answers with decoded temporaries. `search`, `split`, `join`, `insert_min` and `remove_min` work on the packed words directly: the owned variants re-encode the items straight from the old words into new ones and move the left subtrees, and the borrowing variants clone the set first. `heap_bytes` includes the cache if it exists.
+
+use std::{borrow::Borrow, fmt::Debug, hash::{Hash, Hasher}, marker::PhantomData, mem::size_of, ops::Range, sync::OnceLock};
+
+use crate::{Set, GTree, Lent, NonemptySet, NonemptySetMeta, augment::Augmentation, pointer::{PointerFamily, RcFamily}};
+
+/// An unsigned integer type whose values can be bit-packed.
+pub trait PackedInt: Copy + Ord + Debug + Hash {
+    fn to_u64(self) -> u64;
+    /// Convert back a value that was obtained from `to_u64`.
+    fn from_u64(value: u64) -> Self;
+}
+
+macro_rules! impl_packed_int {
+    ($($t:ty),*) => {
+        $(
+            impl PackedInt for $t {
+                fn to_u64(self) -> u64 {
+                    return self as u64;
+                }
+
+                fn from_u64(value: u64) -> Self {
+                    return value as $t;
+                }
+            }
+        )*
+    };
+}
+
+impl_packed_int!(u8, u16, u32, u64);
+
+/// A non-empty set of item-left_subtree pairs with integer items, whose items are bit-packed relative to the least item.
+pub struct NonemptyPackedSet<const K: usize, I: PackedInt, A: Augmentation<I> = (), P: PointerFamily = RcFamily> {
+    // The least item; all items are stored as their difference to it.
+    reference: u64,
+    // The number of bits per difference, at most 64. Zero if the set contains a single item.
+    width: u32,
+    // The differences, in descending order of the items, with `width` bits each, starting at the least significant bit of the first word.
+    packed: Box<[u64]>,
+    // The left subtrees, in the same order as the items.
+    subtrees: Box<[GTree<Self>]>,
+    // The decoded items, created on demand by the methods that lend out references to items.
+    decoded: OnceLock<Box<[I]>>,
+    augmentation: PhantomData<A>,
+}
+
+impl<const K: usize, I: PackedInt, A: Augmentation<I>, P: PointerFamily> NonemptyPackedSet<K, I, A, P> {
+    // Internal helper function: encode a non-empty sequence of items in descending order, given as the values of `to_u64` together with the greatest and the least of them, and their left subtrees.
+    fn pack<Values: Iterator<Item = u64>>(values: Values, greatest: u64, least: u64, subtrees: Vec<GTree<Self>>) -> Self {
+        let width = 64 - (greatest - least).leading_zeros();
+
+        let mut packed = vec![0u64; (subtrees.len() * width as usize).div_ceil(64)];
+        if width > 0 {
+            for (i, value) in values.enumerate() {
+                let difference = value - least;
+                let bit = i * width as usize;
+                let (word, offset) = (bit / 64, (bit % 64) as u32);
+                packed[word] |= difference << offset;
+                if offset + width > 64 {
+                    // The difference continues in the next word.
+                    packed[word + 1] |= difference >> (64 - offset);
+                }
+            }
+        }
+
+        return NonemptyPackedSet {
+            reference: least,
+            width,
+            packed: packed.into_boxed_slice(),
+            subtrees: subtrees.into_boxed_slice(),
+            decoded: OnceLock::new(),
+            augmentation: PhantomData,
+        };
+    }
+
+    // Internal helper function: encode a non-empty sequence of items in descending order, together with their left subtrees.
+    fn new(items: &[I], subtrees: Vec<GTree<Self>>) -> Self {
+        return NonemptyPackedSet::pack(items.iter().map(|item| item.to_u64()), items[0].to_u64(), items[items.len() - 1].to_u64(), subtrees);
+    }
+
+    // Internal helper function: re-encode the items at the given positions, together with the given left subtrees, reading the items from the packed words. Empty if the range is empty.
+    fn part(&self, positions: Range<usize>, subtrees: Vec<GTree<Self>>) -> Set<Self> {
+        if positions.is_empty() {
+            return Set::Empty;
+        } else {
+            let (greatest, least) = (self.value(positions.start), self.value(positions.end - 1));
+            return Set::NonEmpty(NonemptyPackedSet::pack(positions.map(|i| self.value(i)), greatest, least, subtrees));
+        }
+    }
+
+    // Internal helper function: the value of `to_u64` for the item at the given position (0 denoting the *greatest* item).
+    fn value(&self, i: usize) -> u64 {
+        if self.width == 0 {
+            return self.reference;
+        }
+
+        let bit = i * self.width as usize;
+        let (word, offset) = (bit / 64, (bit % 64) as u32);
+        let mut difference = self.packed[word] >> offset;
+        if offset + self.width > 64 {
+            difference |= self.packed[word + 1] << (64 - offset);
+        }
+        if self.width < 64 {
+            difference &= (1 << self.width) - 1;
+        }
+
+        return self.reference + difference;
+    }
+
+    // Internal helper function: decode the item at the given position (0 denoting the *greatest* item).
+    fn item(&self, i: usize) -> I {
+        return I::from_u64(self.value(i));
+    }
+
+    // Internal helper function: decode all items, in descending order.
+    fn items(&self) -> Vec<I> {
+        return (0..self.subtrees.len()).map(|i| self.item(i)).collect();
+    }
+
+    // Internal helper function: the decoded items, decoding them if this has not happened yet.
+    fn decoded(&self) -> &[I] {
+        return self.decoded.get_or_init(|| self.items().into_boxed_slice());
+    }
+
+    // Internal helper function: the number of items that are greater than or equal to the key.
+    fn count_at_least(&self, key: &I) -> usize {
+        // The items are stored in descending order, so the items greater than or equal to the key form a prefix.
+        let (mut low, mut high) = (0, self.subtrees.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.item(mid) >= *key {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        return low;
+    }
+
+    // Internal helper function: the position of the least item that is greater than or equal to the key, if any.
+    fn locate(&self, key: &I) -> Option<usize> {
+        return self.count_at_least(key).checked_sub(1);
+    }
+}
+
+impl<const K: usize, I: PackedInt, A: Augmentation<I>, P: PointerFamily> Clone for NonemptyPackedSet<K, I, A, P> {
+    // Clones are usually created in order to be modified, so they do not inherit the decoded items.
+    fn clone(&self) -> Self {
+        return NonemptyPackedSet {
+            reference: self.reference,
+            width: self.width,
+            packed: self.packed.clone(),
+            subtrees: self.subtrees.clone(),
+            decoded: OnceLock::new(),
+            augmentation: PhantomData,
+        };
+    }
+}
+
+// Formats like an array set, with decoded items.
+impl<const K: usize, I: PackedInt, A: Augmentation<I>, P: PointerFamily> Debug for NonemptyPackedSet<K, I, A, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let data: Vec<_> = self.items().into_iter().zip(self.subtrees.iter()).collect();
+        return f.debug_struct("NonemptyPackedSet").field("data", &data).finish();
+    }
+}
+
+// The encoding is determined by the items, so we can compare encodings instead of items.
+impl<const K: usize, I: PackedInt, A: Augmentation<I>, P: PointerFamily> PartialEq for NonemptyPackedSet<K, I, A, P> {
+    fn eq(&self, other: &Self) -> bool {
+        return self.reference == other.reference && self.width == other.width && self.packed == other.packed && self.subtrees == other.subtrees;
+    }
+}
+
+impl<const K: usize, I: PackedInt, A: Augmentation<I>, P: PointerFamily> Eq for NonemptyPackedSet<K, I, A, P> {}
+
+impl<const K: usize, I: PackedInt, A: Augmentation<I>, P: PointerFamily> Hash for NonemptyPackedSet<K, I, A, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.reference.hash(state);
+        self.width.hash(state);
+        self.packed.hash(state);
+        self.subtrees.hash(state);
+    }
+}
+
+impl<const K: usize, I: PackedInt, A: Augmentation<I>, P: PointerFamily> NonemptySet for NonemptyPackedSet<K, I, A, P> {
+    type Item = I;
+    type Pointers = P;
+    type Augmentation = A;
+    type Hasher = ();
+
+    const TARGET_NODE_SIZE: usize = K;
+
+    fn singleton(item: (Self::Item, GTree<Self>)) -> Self {
+        return NonemptyPackedSet::new(&[item.0], vec![item.1]);
+    }
+
+    // The borrowing methods clone the set, which only copies the packed words and the pointers to the left subtrees, and proceed like the owned ones.
+
+    fn insert_min(&self, new_min: (Self::Item, GTree<Self>)) -> Self {
+        return self.clone().insert_min_owned(new_min);
+    }
+
+    fn remove_min(&self) -> ((Self::Item, GTree<Self>), Set<Self>) {
+        return self.clone().remove_min_owned();
+    }
+
+    fn split(&self, key: &Self::Item) -> (Set<Self>, Option<GTree<Self>> /* left subtree of key (if key is in self, else None) */, Set<Self>) {
+        let (left, found, right) = self.clone().split_owned(key);
+        return (left, found.map(|(_, left_subtree)| left_subtree), right);
+    }
+
+    fn join(left: &Self, right: &Self) -> Self {
+        return NonemptyPackedSet::join_owned(left.clone(), right.clone());
+    }
+
+    // The owned methods re-encode the items straight from the packed words into new ones, and move the left subtrees instead of cloning them.
+
+    fn split_owned(mut self, key: &Self::Item) -> (Set<Self>, Option<(Self::Item, GTree<Self>)>, Set<Self>) {
+        let len = self.subtrees.len();
+        let greater_or_equal = self.count_at_least(key);
+        let mut greater = std::mem::take(&mut self.subtrees).into_vec();
+
+        if greater_or_equal > 0 && self.item(greater_or_equal - 1) == *key {
+            let i = greater_or_equal - 1;
+            let less = greater.split_off(i + 1);
+            let left_subtree = greater.pop().unwrap(/* i + 1 subtrees are left */);
+            return (self.part(i + 1..len, less), Some((self.item(i), left_subtree)), self.part(0..i, greater));
+        } else {
+            let i = greater_or_equal;
+            let less = greater.split_off(i);
+            return (self.part(i..len, less), None, self.part(0..i, greater));
+        }
+    }
+
+    fn join_owned(mut left: Self, mut right: Self) -> Self {
+        let (left_len, right_len) = (left.subtrees.len(), right.subtrees.len());
+        // The greater items of `right` come first, because we store things in reverse order.
+        let mut subtrees = std::mem::take(&mut right.subtrees).into_vec();
+        subtrees.extend(std::mem::take(&mut left.subtrees).into_vec());
+        let values = (0..right_len).map(|i| right.value(i)).chain((0..left_len).map(|i| left.value(i)));
+        return NonemptyPackedSet::pack(values, right.value(0), left.value(left_len - 1), subtrees);
+    }
+
+    fn remove_min_owned(mut self) -> ((Self::Item, GTree<Self>), Set<Self>) {
+        let len = self.subtrees.len();
+        let mut subtrees = std::mem::take(&mut self.subtrees).into_vec();
+        let min = (self.item(len - 1), subtrees.pop().unwrap(/* the set is never empty */));
+        return (min, self.part(0..len - 1, subtrees));
+    }
+
+    fn insert_min_owned(mut self, new_min: (Self::Item, GTree<Self>)) -> Self {
+        let len = self.subtrees.len();
+        let mut subtrees = std::mem::take(&mut self.subtrees).into_vec();
+        subtrees.push(new_min.1);
+        let values = (0..len).map(|i| self.value(i)).chain(std::iter::once(new_min.0.to_u64()));
+        return NonemptyPackedSet::pack(values, self.value(0), new_min.0.to_u64(), subtrees);
+    }
+
+    fn search(&self, key: &Self::Item) -> Option<(Self::Item, GTree<Self>)> {
+        return self.locate(key).map(|i| (self.item(i), self.subtrees[i].clone()));
+    }
+
+    // The references must point into the decoded items, so this decodes the items into the cache.
+    fn search_ref<Q: Ord + ?Sized>(&self, key: &Q) -> Option<(&Self::Item, &GTree<Self>)>
+    where
+        Self::Item: Borrow<Q>,
+    {
+        let decoded = self.decoded();
+        let greater_or_equal = decoded.partition_point(|item| item.borrow() >= key);
+        return greater_or_equal.checked_sub(1).map(|i| (&decoded[i], &self.subtrees[i]));
+    }
+
+    // Compares the key with decoded temporaries, so that lookups via `has` do not create the cache.
+    fn search_left_subtree<Q: Ord + ?Sized>(&self, key: &Q) -> Option<(&GTree<Self>, bool)>
+    where
+        Self::Item: Borrow<Q>,
+    {
+        let (mut low, mut high) = (0, self.subtrees.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.item(mid).borrow() >= key {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        return low.checked_sub(1).map(|i| (&self.subtrees[i], self.item(i).borrow() == key));
+    }
+
+    fn pairs_descending(&self) -> impl Iterator<Item = (&Self::Item, &GTree<Self>)> {
+        return self.decoded().iter().zip(self.subtrees.iter());
+    }
+
+    // Decodes every item into a temporary, so that computing sizes, summaries and statistics does not create the cache.
+    fn pairs_descending_lent(&self) -> impl Iterator<Item = (Lent<'_, Self::Item>, &GTree<Self>)> {
+        return self.subtrees.iter().enumerate().map(|(i, left_subtree)| (Lent::Owned(self.item(i)), left_subtree));
+    }
+
+    fn left_subtrees_descending(&self) -> impl Iterator<Item = &GTree<Self>> {
+        return self.subtrees.iter();
+    }
+
+    fn update_left_subtree<F: FnOnce(&mut GTree<Self>)>(&mut self, key: &Self::Item, f: F) -> bool {
+        match self.locate(key) {
+            None => return false,
+            Some(i) => {
+                f(&mut self.subtrees[i]);
+                return true;
+            }
+        }
+    }
+
+    fn from_descending_pairs(pairs: Vec<(Self::Item, GTree<Self>)>) -> Self {
+        assert!(!pairs.is_empty(), "Cannot create a set from zero pairs.");
+        let (items, subtrees): (Vec<_>, Vec<_>) = pairs.into_iter().unzip();
+        return NonemptyPackedSet::new(&items, subtrees);
+    }
+}
+
+impl<const K: usize, I: PackedInt, A: Augmentation<I>, P: PointerFamily> NonemptySetMeta for NonemptyPackedSet<K, I, A, P> {
+    /// Return a reference to the maximal item in the set.
+    fn get_max(&self) -> &Self::Item {
+        return &self.decoded()[0];
+    }
+
+    /// Return a reference to the minimal item in the set.
+    fn get_min(&self) -> &Self::Item {
+        return &self.decoded()[self.subtrees.len() - 1];
+    }
+
+    fn len(&self) -> usize {
+        return self.subtrees.len();
+    }
+
+    fn get_pair_by_index(&self, index: usize) -> Option<(&Self::Item, &GTree<Self>)> {
+        return self.subtrees.len().checked_sub(index + 1).map(|i| (&self.decoded()[i], &self.subtrees[i]));
+    }
+
+    fn from_descending(items: &[Self::Item]) -> Self {
+        return NonemptyPackedSet::new(items, vec![GTree::Empty; items.len()]);
+    }
+
+    fn item_slot_count(&self) -> usize {
+        return self.subtrees.len();
+    }
+
+    fn heap_bytes(&self) -> usize {
+        let decoded_bytes = self.decoded.get().map_or(0, |decoded| decoded.len() * size_of::<I>());
+        return self.packed.len() * size_of::<u64>() + self.subtrees.len() * size_of::<GTree<Self>>() + decoded_bytes;
+    }
+}
@@ -0,0 +1,84 @@
+// This file abstracts over the reference-counted pointers that G-trees use to share G-nodes (and that k-lists use to share vertices).
+//
+// `Rc` is faster, but trees built from `Rc`s cannot be sent to other threads. With `Arc`, a tree is `Send` and `Sync` whenever its items are, so immutable snapshots can be shared between threads. Each NonemptySet picks a pointer family via `NonemptySet::Pointers`, and G-trees over that set use the same family.
+//
+// Every shared allocation is a G-node or a k-list vertex, so the pointer families report allocations (and modifications via `make_mut`) as block writes to the I/O cost model (see `iocost.rs`).
+
+use std::fmt::Debug;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::iocost;
+
+/// A family of shared pointer types, one for every pointee type.
+pub trait PointerFamily: Clone + Debug {
+    type Pointer<T>: Deref<Target = T> + Clone;
+
+    fn new<T>(value: T) -> Self::Pointer<T>;
+
+    /// Return whether both pointers point to the same allocation.
+    fn ptr_eq<T>(a: &Self::Pointer<T>, b: &Self::Pointer<T>) -> bool;
+
+    /// Return a mutable reference to the pointee, cloning it first if it is shared with other pointers.
+    fn make_mut<T: Clone>(pointer: &mut Self::Pointer<T>) -> &mut T;
+
+    /// Move the pointee out of the pointer if no other pointer shares it, otherwise return the pointer.
+    fn try_unwrap<T>(pointer: Self::Pointer<T>) -> Result<T, Self::Pointer<T>>;
+}
+
+/// Single-threaded pointers: `Rc`.
+#[derive(Debug, Clone, Copy)]
+pub struct RcFamily;
+
+impl PointerFamily for RcFamily {
+    type Pointer<T> = Rc<T>;
+
+    fn new<T>(value: T) -> Rc<T> {
+        let pointer = Rc::new(value);
+        iocost::write(&*pointer);
+        return pointer;
+    }
+
+    fn ptr_eq<T>(a: &Rc<T>, b: &Rc<T>) -> bool {
+        return Rc::ptr_eq(a, b);
+    }
+
+    fn make_mut<T: Clone>(pointer: &mut Rc<T>) -> &mut T {
+        let pointee = Rc::make_mut(pointer);
+        iocost::write(pointee);
+        return pointee;
+    }
+
+    fn try_unwrap<T>(pointer: Rc<T>) -> Result<T, Rc<T>> {
+        return Rc::try_unwrap(pointer);
+    }
+}
+
+/// Thread-safe pointers: `Arc`.
+#[derive(Debug, Clone, Copy)]
+pub struct ArcFamily;
+
+impl PointerFamily for ArcFamily {
+    type Pointer<T> = Arc<T>;
+
+    fn new<T>(value: T) -> Arc<T> {
+        let pointer = Arc::new(value);
+        iocost::write(&*pointer);
+        return pointer;
+    }
+
+    fn ptr_eq<T>(a: &Arc<T>, b: &Arc<T>) -> bool {
+        return Arc::ptr_eq(a, b);
+    }
+
+    fn make_mut<T: Clone>(pointer: &mut Arc<T>) -> &mut T {
+        let pointee = Arc::make_mut(pointer);
+        iocost::write(pointee);
+        return pointee;
+    }
+
+    fn try_unwrap<T>(pointer: Arc<T>) -> Result<T, Arc<T>> {
+        return Arc::try_unwrap(pointer);
+    }
+}
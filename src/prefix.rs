@@ -0,0 +1,512 @@
+// This file implements an inner set for byte-string items that stores its items front coded.
+//
+// Items that share long prefixes, such as paths, waste most of their space on repeating those prefixes. The prefix set stores the items of a G-node in descending order in a single byte buffer, and stores every item only as the length of the prefix it shares with the preceding (greater) item, followed by the remaining suffix. The left subtrees are kept in a parallel buffer. Like the array set, the prefix set stores its pairs in descending order, and K only determines the rank distribution.
+//
+// Decoding an item requires decoding all items before it, so some items, the restart items, are stored in full. Which items are restart items depends only on the items themselves: the greatest item of every set, every item whose hash is divisible by `RESTART_INTERVAL`, and every item that shares no prefix with the preceding item anyway. The encoding of a sequence of items is therefore canonical, and cutting it into pieces preserves the encoding of every piece except for its first item. `split` and `remove_min` cut the byte buffer and the entries at the split point and re-encode only the first item of the lesser part, and `join` and `insert_min` copy the existing encodings and re-encode only the first item of the lesser set. In exchange, the number of items between two restart items is not bounded, only its expectation is.
+//
+// `search`, `split` and `update_left_subtree` first binary-search over the restart items, which can be compared to the key directly, and then walk through a single block of front-coded items. The walk keeps track of the length of the common prefix of the key and the current item, which together with the shared prefix lengths determines most comparisons without looking at any bytes; otherwise it compares the key against the suffix only. No item is materialized, except for the one returned by `search`. `search_left_subtree` accepts any key that a `Vec<u8>` can be borrowed as, which can only be compared with a `Vec<u8>`, so it decodes the items it compares against into a single buffer that it reuses for the whole lookup.
+//
+// As with packed sets, only the methods that lend out references to items (`pairs_descending`, `get_min` and friends) decode the items into a cache, which the set keeps until it is dropped. `pairs_descending_lent` lends out decoded temporaries instead, and `heap_bytes` includes the cache if it exists.
+
+use std::{borrow::Borrow, cmp::Ordering, fmt::Debug, hash::{Hash, Hasher}, marker::PhantomData, mem::size_of, ops::{Bound, Range}, sync::OnceLock};
+
+use crate::{Set, GTree, Lent, NonemptySet, NonemptySetMeta, above_lower, below_upper, augment::{summarize, Augmentation, Monoid, Summary}, pointer::{PointerFamily, RcFamily}};
+
+/// On average, every this many items the prefix set stores an item in full rather than front coded.
+pub const RESTART_INTERVAL: usize = 16;
+
+// The encoding of a single item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Entry {
+    // The length of the prefix that the item shares with the preceding item. Zero for every restart item.
+    shared: u32,
+    // The end of the item's suffix in the byte buffer. The suffix starts at the end of the preceding suffix.
+    end: u32,
+}
+
+// Whether an item is a restart item wherever it occurs. This depends only on the item, so that cutting an encoding into pieces does not move the restart items of the pieces.
+fn is_restart(item: &[u8]) -> bool {
+    // FNV-1a, followed by a multiplication that mixes the well-distributed high bits into the bits we look at.
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in item {
+        hash = (hash ^ (*byte as u64)).wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    let mixed = (hash ^ (hash >> 32)).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    return (mixed >> 32).is_multiple_of(RESTART_INTERVAL as u64);
+}
+
+// The encoding of a sequence of items in descending order, under construction.
+#[derive(Default)]
+struct Encoder {
+    bytes: Vec<u8>,
+    entries: Vec<Entry>,
+    // The positions of all items that are stored in full.
+    restarts: Vec<u32>,
+}
+
+impl Encoder {
+    // Append an item, given the item that precedes it (if any).
+    fn push(&mut self, item: &[u8], previous: Option<&[u8]>) {
+        let shared = match previous {
+            Some(previous) if !is_restart(item) => common_prefix_len(previous, item),
+            _ => 0,
+        };
+        self.push_suffix(shared, &item[shared..]);
+    }
+
+    // Append the encoding of an item, given as the length of the prefix it shares with the preceding item and the remaining suffix.
+    fn push_suffix(&mut self, shared: usize, suffix: &[u8]) {
+        if shared == 0 {
+            self.restarts.push(u32::try_from(self.entries.len()).unwrap());
+        }
+        self.bytes.extend_from_slice(suffix);
+        self.entries.push(Entry {
+            shared: u32::try_from(shared).unwrap(),
+            end: u32::try_from(self.bytes.len()).expect("A prefix set can hold at most 4 GiB of suffixes."),
+        });
+    }
+
+    // Append the encodings of the items at the given positions of `set`, without decoding them. The item that precedes them in `set` must be the last item of the encoder.
+    fn copy<const K: usize, A: Augmentation<Vec<u8>>, P: PointerFamily>(&mut self, set: &NonemptyPrefixSet<K, A, P>, positions: Range<usize>) {
+        for i in positions {
+            self.push_suffix(set.entries[i].shared as usize, set.suffix(i));
+        }
+    }
+
+    fn finish<const K: usize, A: Augmentation<Vec<u8>>, P: PointerFamily>(self, subtrees: Vec<GTree<NonemptyPrefixSet<K, A, P>>>) -> NonemptyPrefixSet<K, A, P> {
+        return NonemptyPrefixSet {
+            bytes: self.bytes.into_boxed_slice(),
+            entries: self.entries.into_boxed_slice(),
+            restarts: self.restarts.into_boxed_slice(),
+            subtrees: subtrees.into_boxed_slice(),
+            decoded: OnceLock::new(),
+            augmentation: PhantomData,
+        };
+    }
+}
+
+/// A non-empty set of item-left_subtree pairs with byte-string items, whose items are front coded.
+pub struct NonemptyPrefixSet<const K: usize, A: Augmentation<Vec<u8>> = (), P: PointerFamily = RcFamily> {
+    // The suffixes of all items, in descending order of the items.
+    bytes: Box<[u8]>,
+    entries: Box<[Entry]>,
+    // The positions of all items that are stored in full, in ascending order. Determined by the entries.
+    restarts: Box<[u32]>,
+    // The left subtrees, in the same order as the items.
+    subtrees: Box<[GTree<Self>]>,
+    // The decoded items, created on demand by the methods that lend out references to items.
+    decoded: OnceLock<Box<[Vec<u8>]>>,
+    augmentation: PhantomData<A>,
+}
+
+impl<const K: usize, A: Augmentation<Vec<u8>>, P: PointerFamily> NonemptyPrefixSet<K, A, P> {
+    // Internal helper function: encode a non-empty sequence of items in descending order, together with their left subtrees.
+    fn new(items: &[Vec<u8>], subtrees: Vec<GTree<Self>>) -> Self {
+        let mut encoder = Encoder::default();
+        for (i, item) in items.iter().enumerate() {
+            encoder.push(item, i.checked_sub(1).map(|previous| &items[previous][..]));
+        }
+        return encoder.finish(subtrees);
+    }
+
+    // Internal helper function: the start of the suffix of the item at the given position (0 denoting the *greatest* item) in the byte buffer.
+    fn start(&self, i: usize) -> usize {
+        return if i == 0 { 0 } else { self.entries[i - 1].end as usize };
+    }
+
+    // Internal helper function: the suffix of the item at the given position.
+    fn suffix(&self, i: usize) -> &[u8] {
+        return &self.bytes[self.start(i)..self.entries[i].end as usize];
+    }
+
+    // Internal helper function: call `f` with every position and item, in descending order, decoding into a single buffer.
+    fn for_each_item<F: FnMut(usize, &Vec<u8>)>(&self, mut f: F) {
+        let mut item = vec![];
+        for (i, entry) in self.entries.iter().enumerate() {
+            item.truncate(entry.shared as usize);
+            item.extend_from_slice(self.suffix(i));
+            f(i, &item);
+        }
+    }
+
+    // Internal helper function: decode the item at the given position, starting from the preceding restart item.
+    fn item(&self, i: usize) -> Vec<u8> {
+        let restart = self.restarts[self.restarts.partition_point(|restart| *restart as usize <= i) - 1] as usize;
+        let mut item = vec![];
+        for j in restart..=i {
+            item.truncate(self.entries[j].shared as usize);
+            item.extend_from_slice(self.suffix(j));
+        }
+        return item;
+    }
+
+    // Internal helper function: decode all items, in descending order.
+    fn items(&self) -> Vec<Vec<u8>> {
+        let mut items = Vec::with_capacity(self.entries.len());
+        self.for_each_item(|_, item| items.push(item.clone()));
+        return items;
+    }
+
+    // Internal helper function: the decoded items, decoding them if this has not happened yet.
+    fn decoded(&self) -> &[Vec<u8>] {
+        return self.decoded.get_or_init(|| self.items().into_boxed_slice());
+    }
+
+    // Internal helper function: the positions of the items of the block that starts at the given restart item.
+    fn block(&self, restart: usize) -> Range<usize> {
+        let start = self.restarts[restart] as usize;
+        let end = self.restarts.get(restart + 1).map_or(self.entries.len(), |end| *end as usize);
+        return start..end;
+    }
+
+    // Internal helper function: the number of items that are greater than or equal to the key.
+    fn count_at_least(&self, key: &[u8]) -> usize {
+        // The items are stored in descending order, so the items greater than or equal to the key form a prefix. First find the last block whose restart item is greater than or equal to the key, comparing against the restart items directly.
+        let (mut low, mut high) = (0, self.restarts.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.suffix(self.restarts[mid] as usize) >= key {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        if low == 0 {
+            return 0;
+        }
+
+        // Then walk through that block. All items from `start` up to `i` are greater than the key, and `matched` is the length of the common prefix of the key and item `i`.
+        let block = self.block(low - 1);
+        let (start, end) = (block.start, block.end);
+        let restart = self.suffix(start);
+        let mut matched = common_prefix_len(restart, key);
+        if restart == key {
+            return start + 1;
+        }
+
+        for i in start + 1..end {
+            let shared = self.entries[i].shared as usize;
+            match shared.cmp(&matched) {
+                // The item agrees with the preceding one beyond the point where that one exceeds the key, so it exceeds the key as well.
+                Ordering::Greater => {}
+                // The item agrees with the key up to `shared`, and then falls below the preceding item, which agrees with the key at that point.
+                Ordering::Less => return i,
+                Ordering::Equal => {
+                    let suffix = self.suffix(i);
+                    let rest = &key[matched..];
+                    match suffix.cmp(rest) {
+                        Ordering::Less => return i,
+                        Ordering::Equal => return i + 1,
+                        Ordering::Greater => matched += common_prefix_len(suffix, rest),
+                    }
+                }
+            }
+        }
+
+        return end;
+    }
+
+    // Internal helper function: the position of the least item that is greater than or equal to the key, if any.
+    fn locate(&self, key: &[u8]) -> Option<usize> {
+        return self.count_at_least(key).checked_sub(1);
+    }
+
+    // Internal helper function: encode the items from the given position on, with their left subtrees. Only the first item is decoded and re-encoded, the others keep their encodings.
+    fn lesser_part(&self, from: usize, subtrees: Vec<GTree<Self>>) -> Set<Self> {
+        if from == self.entries.len() {
+            return Set::Empty;
+        }
+        let mut encoder = Encoder::default();
+        encoder.push(&self.item(from), None);
+        encoder.copy(self, from + 1..self.entries.len());
+        return Set::NonEmpty(encoder.finish(subtrees));
+    }
+
+    // Internal helper function: the encoding of the items before the given position, taken out of the set. This is a prefix of the encoding of the set.
+    fn take_greater_part(&mut self, to: usize) -> Option<Encoder> {
+        if to == 0 {
+            return None;
+        }
+        let mut bytes = std::mem::take(&mut self.bytes).into_vec();
+        bytes.truncate(self.entries[to - 1].end as usize);
+        let mut entries = std::mem::take(&mut self.entries).into_vec();
+        entries.truncate(to);
+        let mut restarts = std::mem::take(&mut self.restarts).into_vec();
+        restarts.truncate(restarts.partition_point(|restart| (*restart as usize) < to));
+        return Some(Encoder { bytes, entries, restarts });
+    }
+}
+
+// Internal helper function: the length of the longest common prefix of two byte strings.
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    return a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count();
+}
+
+impl<const K: usize, A: Augmentation<Vec<u8>>, P: PointerFamily> Clone for NonemptyPrefixSet<K, A, P> {
+    // Clones are usually created in order to be modified, so they do not inherit the decoded items.
+    fn clone(&self) -> Self {
+        return NonemptyPrefixSet {
+            bytes: self.bytes.clone(),
+            entries: self.entries.clone(),
+            restarts: self.restarts.clone(),
+            subtrees: self.subtrees.clone(),
+            decoded: OnceLock::new(),
+            augmentation: PhantomData,
+        };
+    }
+}
+
+// Formats like an array set, with decoded items.
+impl<const K: usize, A: Augmentation<Vec<u8>>, P: PointerFamily> Debug for NonemptyPrefixSet<K, A, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let data: Vec<_> = self.items().into_iter().zip(self.subtrees.iter()).collect();
+        return f.debug_struct("NonemptyPrefixSet").field("data", &data).finish();
+    }
+}
+
+// The encoding is determined by the items, so we can compare encodings instead of items. The restart positions are determined by the entries.
+impl<const K: usize, A: Augmentation<Vec<u8>>, P: PointerFamily> PartialEq for NonemptyPrefixSet<K, A, P> {
+    fn eq(&self, other: &Self) -> bool {
+        return self.bytes == other.bytes && self.entries == other.entries && self.subtrees == other.subtrees;
+    }
+}
+
+impl<const K: usize, A: Augmentation<Vec<u8>>, P: PointerFamily> Eq for NonemptyPrefixSet<K, A, P> {}
+
+impl<const K: usize, A: Augmentation<Vec<u8>>, P: PointerFamily> Hash for NonemptyPrefixSet<K, A, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bytes.hash(state);
+        self.entries.hash(state);
+        self.subtrees.hash(state);
+    }
+}
+
+impl<const K: usize, A: Augmentation<Vec<u8>>, P: PointerFamily> NonemptySet for NonemptyPrefixSet<K, A, P> {
+    type Item = Vec<u8>;
+    type Pointers = P;
+    type Augmentation = A;
+    type Hasher = ();
+
+    const TARGET_NODE_SIZE: usize = K;
+
+    fn singleton(item: (Self::Item, GTree<Self>)) -> Self {
+        return NonemptyPrefixSet::new(&[item.0], vec![item.1]);
+    }
+
+    // The borrowing methods clone the set, which only copies the encoding and the pointers to the left subtrees, and proceed like the owned ones.
+
+    fn insert_min(&self, new_min: (Self::Item, GTree<Self>)) -> Self {
+        return self.clone().insert_min_owned(new_min);
+    }
+
+    fn remove_min(&self) -> ((Self::Item, GTree<Self>), Set<Self>) {
+        return self.clone().remove_min_owned();
+    }
+
+    fn split(&self, key: &Self::Item) -> (Set<Self>, Option<GTree<Self>> /* left subtree of key (if key is in self, else None) */, Set<Self>) {
+        let (left, found, right) = self.clone().split_owned(key);
+        return (left, found.map(|(_, left_subtree)| left_subtree), right);
+    }
+
+    fn join(left: &Self, right: &Self) -> Self {
+        return NonemptyPrefixSet::join_owned(left.clone(), right.clone());
+    }
+
+    // The owned methods cut and extend the encodings of their sets, and move the left subtrees instead of cloning them.
+
+    fn split_owned(mut self, key: &Self::Item) -> (Set<Self>, Option<(Self::Item, GTree<Self>)>, Set<Self>) {
+        let greater_or_equal = self.count_at_least(key);
+        let found = greater_or_equal > 0 && self.item(greater_or_equal - 1) == *key;
+        // The greater part consists of the items before position `to`, the lesser part of the items from position `from` on.
+        let (to, from) = if found { (greater_or_equal - 1, greater_or_equal) } else { (greater_or_equal, greater_or_equal) };
+
+        let mut greater_subtrees = std::mem::take(&mut self.subtrees).into_vec();
+        let lesser = self.lesser_part(from, greater_subtrees.split_off(from));
+        let found = if found {
+            Some((key.clone(), greater_subtrees.pop().unwrap(/* `to + 1` subtrees are left */)))
+        } else {
+            None
+        };
+        let greater = match self.take_greater_part(to) {
+            None => Set::Empty,
+            Some(encoder) => Set::NonEmpty(encoder.finish(greater_subtrees)),
+        };
+        return (lesser, found, greater);
+    }
+
+    fn join_owned(mut left: Self, mut right: Self) -> Self {
+        let right_len = right.entries.len();
+        let right_min = right.item(right_len - 1);
+        let mut subtrees = std::mem::take(&mut right.subtrees).into_vec();
+        subtrees.extend(std::mem::take(&mut left.subtrees).into_vec());
+
+        // The greater items of `right` come first, because we store things in reverse order. The first item of `left` is stored in full, and must now be encoded relative to the least item of `right`.
+        let mut encoder = right.take_greater_part(right_len).unwrap(/* the set is never empty */);
+        encoder.push(left.suffix(0), Some(&right_min));
+        encoder.copy(&left, 1..left.entries.len());
+        return encoder.finish(subtrees);
+    }
+
+    fn remove_min_owned(mut self) -> ((Self::Item, GTree<Self>), Set<Self>) {
+        let len = self.entries.len();
+        let mut subtrees = std::mem::take(&mut self.subtrees).into_vec();
+        let min = (self.item(len - 1), subtrees.pop().unwrap(/* the set is never empty */));
+        let others = match self.take_greater_part(len - 1) {
+            None => Set::Empty,
+            Some(encoder) => Set::NonEmpty(encoder.finish(subtrees)),
+        };
+        return (min, others);
+    }
+
+    fn insert_min_owned(mut self, new_min: (Self::Item, GTree<Self>)) -> Self {
+        let len = self.entries.len();
+        let previous = self.item(len - 1);
+        let mut subtrees = std::mem::take(&mut self.subtrees).into_vec();
+        subtrees.push(new_min.1);
+
+        let mut encoder = self.take_greater_part(len).unwrap(/* the set is never empty */);
+        encoder.push(&new_min.0, Some(&previous));
+        return encoder.finish(subtrees);
+    }
+
+    fn search(&self, key: &Self::Item) -> Option<(Self::Item, GTree<Self>)> {
+        return self.locate(key).map(|i| (self.item(i), self.subtrees[i].clone()));
+    }
+
+    // The references must point into the decoded items, so this decodes the items into the cache.
+    fn search_ref<Q: Ord + ?Sized>(&self, key: &Q) -> Option<(&Self::Item, &GTree<Self>)>
+    where
+        Self::Item: Borrow<Q>,
+    {
+        let decoded = self.decoded();
+        let greater_or_equal = decoded.partition_point(|item| item.borrow() >= key);
+        return greater_or_equal.checked_sub(1).map(|i| (&decoded[i], &self.subtrees[i]));
+    }
+
+    // Compares the key with items decoded into a single buffer, so that lookups via `has` do not create the cache.
+    fn search_left_subtree<Q: Ord + ?Sized>(&self, key: &Q) -> Option<(&GTree<Self>, bool)>
+    where
+        Self::Item: Borrow<Q>,
+    {
+        let mut item = vec![];
+
+        // Find the last block whose restart item is greater than or equal to the key.
+        let (mut low, mut high) = (0, self.restarts.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            item.clear();
+            item.extend_from_slice(self.suffix(self.restarts[mid] as usize));
+            if Borrow::<Q>::borrow(&item) >= key {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        if low == 0 {
+            return None;
+        }
+
+        // Decode that block until reaching an item that is less than the key.
+        let block = self.block(low - 1);
+        let mut found = (block.start, false);
+        for i in block {
+            item.truncate(self.entries[i].shared as usize);
+            item.extend_from_slice(self.suffix(i));
+            match Borrow::<Q>::borrow(&item).cmp(key) {
+                Ordering::Less => break,
+                Ordering::Equal => {
+                    found = (i, true);
+                    break;
+                }
+                Ordering::Greater => found = (i, false),
+            }
+        }
+        return Some((&self.subtrees[found.0], found.1));
+    }
+
+    fn pairs_descending(&self) -> impl Iterator<Item = (&Self::Item, &GTree<Self>)> {
+        return self.decoded().iter().zip(self.subtrees.iter());
+    }
+
+    // Decodes the items one after the other into a buffer, and lends out a copy of each.
+    fn pairs_descending_lent(&self) -> impl Iterator<Item = (Lent<'_, Self::Item>, &GTree<Self>)> {
+        return self.entries.iter().enumerate().scan(vec![], move |item, (i, entry)| {
+            item.truncate(entry.shared as usize);
+            item.extend_from_slice(self.suffix(i));
+            return Some((Lent::Owned(item.clone()), &self.subtrees[i]));
+        });
+    }
+
+    fn left_subtrees_descending(&self) -> impl Iterator<Item = &GTree<Self>> {
+        return self.subtrees.iter();
+    }
+
+    fn update_left_subtree<F: FnOnce(&mut GTree<Self>)>(&mut self, key: &Self::Item, f: F) -> bool {
+        match self.locate(key) {
+            None => return false,
+            Some(i) => {
+                f(&mut self.subtrees[i]);
+                return true;
+            }
+        }
+    }
+
+    fn from_descending_pairs(pairs: Vec<(Self::Item, GTree<Self>)>) -> Self {
+        assert!(!pairs.is_empty(), "Cannot create a set from zero pairs.");
+        let (items, subtrees): (Vec<_>, Vec<_>) = pairs.into_iter().unzip();
+        return NonemptyPrefixSet::new(&items, subtrees);
+    }
+
+    // The following methods are called for every new G-node, so they decode into a single buffer rather than into temporaries.
+
+    fn summary(&self) -> Summary<Self> {
+        let mut ret = Summary::<Self>::neutral();
+        self.for_each_item(|i, item| {
+            ret = self.subtrees[i].summary().combine(&summarize::<Self>(item)).combine(&ret);
+        });
+        return ret;
+    }
+
+    fn fold_pairs(&self, lower: Bound<&Vec<u8>>, upper: Bound<&Vec<u8>>) -> Summary<Self> {
+        let mut ret = Summary::<Self>::neutral();
+        self.for_each_item(|i, item| {
+            if above_lower(item, lower) && below_upper(item, upper) {
+                ret = self.subtrees[i].summary().combine(&summarize::<Self>(item)).combine(&ret);
+            }
+        });
+        return ret;
+    }
+}
+
+impl<const K: usize, A: Augmentation<Vec<u8>>, P: PointerFamily> NonemptySetMeta for NonemptyPrefixSet<K, A, P> {
+    /// Return a reference to the maximal item in the set.
+    fn get_max(&self) -> &Self::Item {
+        return &self.decoded()[0];
+    }
+
+    /// Return a reference to the minimal item in the set.
+    fn get_min(&self) -> &Self::Item {
+        return &self.decoded()[self.subtrees.len() - 1];
+    }
+
+    fn len(&self) -> usize {
+        return self.subtrees.len();
+    }
+
+    fn get_pair_by_index(&self, index: usize) -> Option<(&Self::Item, &GTree<Self>)> {
+        return self.subtrees.len().checked_sub(index + 1).map(|i| (&self.decoded()[i], &self.subtrees[i]));
+    }
+
+    fn from_descending(items: &[Self::Item]) -> Self {
+        return NonemptyPrefixSet::new(items, vec![GTree::Empty; items.len()]);
+    }
+
+    fn item_slot_count(&self) -> usize {
+        return self.subtrees.len();
+    }
+
+    fn heap_bytes(&self) -> usize {
+        let decoded_bytes = self.decoded.get().map_or(0, |decoded| decoded.iter().map(|item| size_of::<Vec<u8>>() + item.capacity()).sum());
+        return self.bytes.len() + self.entries.len() * size_of::<Entry>() + self.restarts.len() * size_of::<u32>() + self.subtrees.len() * size_of::<GTree<Self>>() + decoded_bytes;
+    }
+}
@@ -0,0 +1,141 @@
+// This file implements the derivation of ranks from items.
+//
+// G-trees expect the ranks of their items to be drawn from a geometric distribution whose parameter is tied to the targeted G-node size K: an item should have rank at least r with probability (1 / (K + 1))^r. Deriving the rank from a hash of the item (rather than from a random number generator) makes the shape of a G-tree a function of its item set alone.
+//
+// For the shape to stay the same across platforms and Rust releases (for example, for root hashes and stored trees), the hash function must be fixed. `DefaultHasher` is explicitly unspecified, so we use `StableHasher` instead: FNV-1a over the bytes fed to it (integers in little-endian byte order), followed by the finalizer of MurmurHash3, so that the low digits that determine the rank depend on all bits of the item.
+
+use std::hash::{Hash, Hasher};
+
+/// A way of assigning a rank to every item.
+pub trait RankDerivation<Item> {
+    fn rank(&self, item: &Item) -> u8;
+}
+
+/// Derive ranks from the 64 bit `StableHasher` hash of an item, with a geometric distribution targeting G-nodes of size `target_node_size`.
+#[derive(Debug, Clone, Copy)]
+pub struct HashRank {
+    target_node_size: usize,
+}
+
+impl HashRank {
+    pub fn new(target_node_size: usize) -> Self {
+        if target_node_size == 0 {
+            panic!("The target node size must be at least one.");
+        }
+
+        return HashRank { target_node_size };
+    }
+}
+
+impl<Item: Hash> RankDerivation<Item> for HashRank {
+    fn rank(&self, item: &Item) -> u8 {
+        let mut hasher = StableHasher::new();
+        item.hash(&mut hasher);
+        return rank_from_hash(hasher.finish(), self.target_node_size);
+    }
+}
+
+/// A fixed 64 bit hash function, which yields the same hashes on every platform and with every Rust release (as long as the `Hash` implementation of the hashed type stays the same).
+#[derive(Debug, Clone, Copy)]
+pub struct StableHasher {
+    state: u64,
+}
+
+impl StableHasher {
+    pub fn new() -> Self {
+        return StableHasher { state: 0xcbf2_9ce4_8422_2325 };
+    }
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        return StableHasher::new();
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.state ^= *byte as u64;
+            self.state = self.state.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    // The default implementations use the native byte order.
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    // Hash `usize` as `u64`, so that the hashes do not depend on the pointer width.
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write_u16(i as u16);
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32);
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write_u128(i as u128);
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        // The finalizer of MurmurHash3.
+        let mut hash = self.state;
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+        hash ^= hash >> 33;
+        return hash;
+    }
+}
+
+/// Compute a rank from a uniformly distributed hash: the number of trailing zero digits of the hash, written in base `target_node_size + 1`.
+///
+/// If the base is a power of two, this is simply the number of trailing zero bits divided by the number of bits per digit.
+pub fn rank_from_hash(hash: u64, target_node_size: usize) -> u8 {
+    let base = (target_node_size as u64) + 1;
+
+    if base.is_power_of_two() {
+        let bits_per_digit = base.trailing_zeros();
+        return (hash.trailing_zeros() / bits_per_digit) as u8;
+    } else {
+        if hash == 0 {
+            // Every digit is zero, so we return the number of digits.
+            return u64::MAX.ilog(base) as u8;
+        }
+
+        let mut remaining = hash;
+        let mut rank = 0;
+        while remaining.is_multiple_of(base) {
+            remaining /= base;
+            rank += 1;
+        }
+        return rank;
+    }
+}
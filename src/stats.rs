@@ -1,49 +1,75 @@
 use std::fmt::Debug;
+use std::hash::Hash;
 
-use rand::prelude::random;
-use rand_distr::{Distribution, Geometric, Standard};
+use rand::prelude::{random, thread_rng, Rng, SliceRandom};
 
-use gtree_experiments::{*, klist::*};
+#[cfg(feature = "iocost")]
+use gtree_experiments::iocost::*;
+use gtree_experiments::{*, array::*, klist::*, memory::*, nested::NonemptyNestedSet, packed::*, prefix::*, rank::*};
 
-fn create_gtree<S: NonemptySet + Debug>(items: &[(S::Item, u8 /*rank*/)]) -> GTree<S> where S::Item: Clone {
-    let mut t = GTree::Empty;
+fn create_gtree<S: NonemptySet + Debug, R: RankDerivation<S::Item>>(items: &[S::Item], ranker: &R) -> GTree<S> where S::Item: Clone + Ord {
+    let mut sorted = items.to_vec();
+    sorted.sort();
+    sorted.dedup();
 
-    for (item, rank) in items {
-        t = insert(&t, item.clone(), *rank);
-    }
-
-    return t;
+    return GTree::from_sorted_ranked(sorted.into_iter().map(|item| {
+        let rank = ranker.rank(&item);
+        return (item, rank);
+    }));
 }
 
-fn random_gtree_of_size<S: NonemptySet + Debug>(n: usize, target_node_size: usize) -> GTree<S> where S::Item: Clone, Standard: Distribution<S::Item> {
+fn random_gtree_of_size<S: NonemptySet + Debug>(n: usize, target_node_size: usize, random_item: fn() -> S::Item) -> GTree<S> where S::Item: Clone + Ord + Hash {
     let mut items = vec![];
-    let geo = Geometric::new(1.0 - (1.0 / ((target_node_size + 1) as f64))).unwrap();
 
     for _ in 0..n {
-        let key: S::Item = random();
-        let rank = geo.sample(&mut rand::thread_rng()) as u8;
+        let key: S::Item = random_item();
+        items.push(key);
+    }
+
+    return create_gtree(&items[..], &HashRank::new(target_node_size));
+}
+
+// Arrays and packed sets store each G-node in a single buffer, so their physical height is their G-node height.
+fn single_buffer_physical_height<S: NonemptySetMeta>(t: &GTree<S>) -> usize where S::Item: Clone + Ord + Debug {
+    let (stats, _ranks) = gtree_stats(t);
+    return stats.gnode_height;
+}
 
-        items.push((key, rank));
+// A random file path, such that many paths share long prefixes.
+fn random_path() -> Vec<u8> {
+    const DIRECTORIES: [&str; 8] = ["home", "alice", "projects", "gtree_experiments", "src", "target", "release", "deps"];
+    let mut rng = thread_rng();
+
+    let mut path = String::new();
+    for _ in 0..rng.gen_range(2..6) {
+        path.push('/');
+        path.push_str(DIRECTORIES.choose(&mut rng).unwrap());
     }
+    path.push_str(&format!("/{:08x}.rs", rng.gen::<u32>()));
 
-    return create_gtree(&items[..]);
+    return path.into_bytes();
 }
 
-fn random_klist_tree<const K: usize, T: Clone + Ord + Debug>(size: usize) -> GTree<NonemptyReverseKList<K, T>> where Standard: Distribution<T> {
-    return random_gtree_of_size(size, K);
+// Items that are stored as they are own their heap memory, which `memory_report` does not count on its own.
+fn memory_report_with_buffers<S: NonemptySetMeta<Item = Vec<u8>>>(t: &GTree<S>) -> MemoryReport {
+    return memory_report_with_items(t, |item| item.capacity());
 }
 
-fn repeated_experiment<const K: usize, T: Clone + Ord + Debug>(size: usize, repetitions: usize) where Standard: Distribution<T> {
-    let mut results: Vec<(Stats<T>, usize /* physical height */)> = vec![];
+// Run the experiment for trees whose inner sets are of type S, which is described by `set_name` in the output.
+// Items are generated by `random_item`, and the memory of a tree is measured by `measure_memory`.
+fn repeated_experiment<S: NonemptySetMeta>(set_name: &str, size: usize, repetitions: usize, random_item: fn() -> S::Item, physical_height: fn(&GTree<S>) -> usize, measure_memory: fn(&GTree<S>) -> MemoryReport) where S::Item: Clone + Ord + Debug + Hash {
+    let k = S::TARGET_NODE_SIZE;
+    let mut results: Vec<(Stats<S::Item>, usize /* physical height */, MemoryReport)> = vec![];
 
     for _ in 0..repetitions {
-        let tree: GTree<NonemptyReverseKList<K, T>> = random_klist_tree(size);
+        let tree: GTree<S> = random_gtree_of_size(size, k, random_item);
         let (stats, _ranks) = gtree_stats(&tree);
         let phy_height = physical_height(&tree);
-        results.push((stats, phy_height));
+        let memory = measure_memory(&tree);
+        results.push((stats, phy_height, memory));
     }
 
-    let perfect_height = (size as f64).log((K + 1) as f64).ceil();
+    let perfect_height = (size as f64).log((k + 1) as f64).ceil();
 
     // Add together all stats, then divide by number of repetitions to obtain averages.
 
@@ -56,8 +82,9 @@ fn repeated_experiment<const K: usize, T: Clone + Ord + Debug>(size: usize, repe
     let mut height_amplification = 0.0f64;
     let mut average_gnode_size = 0.0f64;
     let mut max_rank = 0.0f64;
+    let mut bytes_per_item = 0.0f64;
 
-    for (stats, phy_height) in results.iter() {
+    for (stats, phy_height, memory) in results.iter() {
         gnode_height += stats.gnode_height as f64;
         gnode_count += stats.gnode_count as f64;
         item_count += stats.item_count as f64;
@@ -67,6 +94,7 @@ fn repeated_experiment<const K: usize, T: Clone + Ord + Debug>(size: usize, repe
         height_amplification += (*phy_height as f64) / perfect_height;
         average_gnode_size += (item_count as f64) / (gnode_count as f64);
         max_rank += stats.rank as f64;
+        bytes_per_item += memory.bytes_per_item();
     }
 
     gnode_height /= repetitions as f64;
@@ -78,6 +106,7 @@ fn repeated_experiment<const K: usize, T: Clone + Ord + Debug>(size: usize, repe
     height_amplification /= repetitions as f64;
     average_gnode_size /= repetitions as f64;
     max_rank /= repetitions as f64;
+    bytes_per_item /= repetitions as f64;
 
     // Add together squares of deviations from means all stats, then divide by number of repetitions to obtain variances.
 
@@ -90,8 +119,9 @@ fn repeated_experiment<const K: usize, T: Clone + Ord + Debug>(size: usize, repe
     let mut variance_average_gnode_size = 0.0f64;
     let mut variance_height_amplification = 0.0f64;
     let mut variance_max_rank = 0.0f64;
+    let mut variance_bytes_per_item = 0.0f64;
 
-    for (stats, phy_height) in results.iter() {
+    for (stats, phy_height, memory) in results.iter() {
         variance_gnode_height += ((stats.gnode_height as f64) - gnode_height) * ((stats.gnode_height as f64) - gnode_height);
         variance_gnode_count += ((stats.gnode_count as f64) - gnode_count) * ((stats.gnode_count as f64) - gnode_count);
         variance_item_count += ((stats.item_count as f64) - item_count) * ((stats.item_count as f64) - item_count);
@@ -101,6 +131,7 @@ fn repeated_experiment<const K: usize, T: Clone + Ord + Debug>(size: usize, repe
         variance_height_amplification += (((*phy_height as f64) / perfect_height) - height_amplification) * (((*phy_height as f64) / perfect_height) - height_amplification);
         variance_average_gnode_size += (((item_count as f64) / (gnode_count as f64)) - average_gnode_size) * (((item_count as f64) / (gnode_count as f64)) - average_gnode_size);
         variance_max_rank += ((stats.rank as f64) - max_rank) * ((stats.rank as f64) - max_rank);
+        variance_bytes_per_item += (memory.bytes_per_item() - bytes_per_item) * (memory.bytes_per_item() - bytes_per_item);
     }
 
     variance_gnode_height /= repetitions as f64;
@@ -112,13 +143,15 @@ fn repeated_experiment<const K: usize, T: Clone + Ord + Debug>(size: usize, repe
     variance_height_amplification /= repetitions as f64;
     variance_average_gnode_size /= repetitions as f64;
     variance_max_rank /= repetitions as f64;
+    variance_bytes_per_item /= repetitions as f64;
 
-    println!("n = {}; K = {}; {} repetitions", size, K, repetitions);
+    println!("{}; n = {}; K = {}; {} repetitions", set_name, size, k, repetitions);
     println!("Legend: name <value> (<variance>)");
     println!("---------------------------------------");
     println!("Item count: {:#?} ({:#?})", item_count, variance_item_count);
     println!("Item slot count: {:#?} ({:#?})", item_slot_count, variance_item_slot_count);
     println!("Space amplification: {:#?} ({:#?})", space_amplification, variance_space_amplification);
+    println!("Bytes per item: {:#?} ({:#?})", bytes_per_item, variance_bytes_per_item);
     println!("G-node count: {:#?} ({:#?})", gnode_count, variance_gnode_count);
     println!("Average G-node size: {:#?} ({:#?})", average_gnode_size, variance_average_gnode_size);
     println!("Maximum rank: {:#?} ({:#?})", max_rank, variance_max_rank);
@@ -129,11 +162,106 @@ fn repeated_experiment<const K: usize, T: Clone + Ord + Debug>(size: usize, repe
     println!("\n\n");
 }
 
+// Compare k-lists of byte strings against prefix sets.
+fn string_experiments<const K: usize>(n: usize) {
+    repeated_experiment::<NonemptyReverseKList<K, Vec<u8>>>("k-list", n, 200, random_path, physical_height, memory_report_with_buffers);
+    repeated_experiment::<NonemptyPrefixSet<K>>("prefix", n, 200, random_path, single_buffer_physical_height, memory_report);
+}
+
+// Measure the block transfers of random operations on a random tree of `size` items whose inner sets are of type S, with an LRU buffer of `buffer_blocks` blocks that stays warm across operations.
+// All operations work on the same tree; the results of updates are discarded.
+#[cfg(feature = "iocost")]
+fn io_experiment<S: NonemptySet<Item = u64> + Debug>(set_name: &str, size: usize, operations: usize, buffer_blocks: usize) {
+    let k = S::TARGET_NODE_SIZE;
+    let ranker = HashRank::new(k);
+    let tree: GTree<S> = random_gtree_of_size(size, k, random);
+    let items: Vec<u64> = tree.iter().cloned().collect();
+    let mut rng = thread_rng();
+    let mut model = IoModel::new(buffer_blocks);
+
+    let mut results: [(&str, Vec<IoCounts>); 6] = [
+        ("has (present)", vec![]),
+        ("has (absent)", vec![]),
+        ("insert", vec![]),
+        ("delete", vec![]),
+        ("unzip", vec![]),
+        ("zip2", vec![]),
+    ];
+
+    for _ in 0..operations {
+        let present = *items.choose(&mut rng).unwrap();
+        let absent: u64 = random();
+
+        results[0].1.push(model.measure(|| has(&tree, &present)).1);
+        results[1].1.push(model.measure(|| has(&tree, &absent)).1);
+        results[2].1.push(model.measure(|| insert(&tree, absent, ranker.rank(&absent))).1);
+        results[3].1.push(model.measure(|| delete(&tree, &present)).1);
+        let ((left, right), counts) = model.measure(|| unzip(&tree, &absent));
+        results[4].1.push(counts);
+        results[5].1.push(model.measure(|| zip2(&left, &right)).1);
+    }
+
+    println!("{}; n = {}; K = {}; buffer of {} blocks; {} operations each", set_name, size, k, buffer_blocks, operations);
+    println!("Legend: operation: mean reads, mean writes, mean I/Os, 99th percentile I/Os, maximum I/Os");
+    println!("---------------------------------------");
+    for (name, counts) in results.iter() {
+        let mut totals: Vec<usize> = counts.iter().map(IoCounts::total).collect();
+        totals.sort();
+
+        let mean_reads = counts.iter().map(|c| c.reads).sum::<usize>() as f64 / counts.len() as f64;
+        let mean_writes = counts.iter().map(|c| c.writes).sum::<usize>() as f64 / counts.len() as f64;
+        let percentile_99 = totals[(totals.len() * 99).div_ceil(100) - 1];
+        println!("{}: {:.2}, {:.2}, {:.2}, {}, {}", name, mean_reads, mean_writes, mean_reads + mean_writes, percentile_99, totals[totals.len() - 1]);
+    }
+    println!("\n\n");
+}
+
+// With the argument `strings`, compare inner sets for byte-string items rather than integer items.
 pub fn main() {
+    // With the argument `io` (optionally followed by a buffer size in blocks), report the block transfers of individual operations on k-lists.
+    #[cfg(feature = "iocost")]
+    if std::env::args().nth(1).as_deref() == Some("io") {
+        let buffer_blocks = std::env::args().nth(2).map_or(0, |blocks| blocks.parse().expect("The buffer size must be a number of blocks."));
+        for n in [1000, 10000, 100000] {
+            io_experiment::<NonemptyReverseKList<1, u64>>("k-list", n, 1000, buffer_blocks);
+            io_experiment::<NonemptyReverseKList<3, u64>>("k-list", n, 1000, buffer_blocks);
+            io_experiment::<NonemptyReverseKList<15, u64>>("k-list", n, 1000, buffer_blocks);
+            io_experiment::<NonemptyReverseKList<63, u64>>("k-list", n, 1000, buffer_blocks);
+        }
+        return;
+    }
+
+    #[cfg(not(feature = "iocost"))]
+    if std::env::args().nth(1).as_deref() == Some("io") {
+        panic!("Counting block transfers requires the `iocost` feature: cargo run --features iocost --bin stats -- io");
+    }
+
+    if std::env::args().nth(1).as_deref() == Some("strings") {
+        for n in [10, 100, 1000, 10000, 100000] {
+            string_experiments::<1>(n);
+            string_experiments::<3>(n);
+            string_experiments::<15>(n);
+            string_experiments::<63>(n);
+        }
+        return;
+    }
+
     for n in [10, 100, 1000, 10000, 100000] {
-        repeated_experiment::<1, u64>(n, 200);
-        repeated_experiment::<3, u64>(n, 200);
-        repeated_experiment::<15, u64>(n, 200);
-        repeated_experiment::<63, u64>(n, 200);
+        repeated_experiment::<NonemptyReverseKList<1, u64>>("k-list", n, 200, random, physical_height, memory_report);
+        repeated_experiment::<NonemptyReverseArray<1, u64>>("array", n, 200, random, single_buffer_physical_height, memory_report);
+        repeated_experiment::<NonemptyPackedSet<1, u64>>("packed", n, 200, random, single_buffer_physical_height, memory_report);
+        repeated_experiment::<NonemptyNestedSet<1, 4, u64>>("nested", n, 200, random, nested::physical_height, memory_report);
+        repeated_experiment::<NonemptyReverseKList<3, u64>>("k-list", n, 200, random, physical_height, memory_report);
+        repeated_experiment::<NonemptyReverseArray<3, u64>>("array", n, 200, random, single_buffer_physical_height, memory_report);
+        repeated_experiment::<NonemptyPackedSet<3, u64>>("packed", n, 200, random, single_buffer_physical_height, memory_report);
+        repeated_experiment::<NonemptyNestedSet<3, 4, u64>>("nested", n, 200, random, nested::physical_height, memory_report);
+        repeated_experiment::<NonemptyReverseKList<15, u64>>("k-list", n, 200, random, physical_height, memory_report);
+        repeated_experiment::<NonemptyReverseArray<15, u64>>("array", n, 200, random, single_buffer_physical_height, memory_report);
+        repeated_experiment::<NonemptyPackedSet<15, u64>>("packed", n, 200, random, single_buffer_physical_height, memory_report);
+        repeated_experiment::<NonemptyNestedSet<15, 4, u64>>("nested", n, 200, random, nested::physical_height, memory_report);
+        repeated_experiment::<NonemptyReverseKList<63, u64>>("k-list", n, 200, random, physical_height, memory_report);
+        repeated_experiment::<NonemptyReverseArray<63, u64>>("array", n, 200, random, single_buffer_physical_height, memory_report);
+        repeated_experiment::<NonemptyPackedSet<63, u64>>("packed", n, 200, random, single_buffer_physical_height, memory_report);
+        repeated_experiment::<NonemptyNestedSet<63, 4, u64>>("nested", n, 200, random, nested::physical_height, memory_report);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,272 @@
+// This file implements a conformance test suite for inner sets, enabled by the `testing` feature.
+//
+// `check_conformance` applies a sequence of operations both to an inner set of the type under test and to a `ControlSet`, and after every step asserts that the two sets contain the same pairs. The consuming variants of `split`, `join` and `remove_min` are applied to a clone of the set and checked against the same control set. Unlike `sets_assert_eq`, the comparison includes the left subtrees: operations can attach a non-empty left subtree to an item, which contains exactly that item, so that a set that loses a subtree or attaches it to the wrong item fails the comparison.
+//
+// Fuzz targets obtain the operations from the fuzzer (`ConformanceOperation` implements `Arbitrary`), whereas `check_random_conformance` generates them from a seeded RNG, so that the suite runs under plain `cargo test` (see `tests/conformance.rs`).
+//
+// The module also provides `TestHasher`, a Merkle hasher for the tests and fuzz targets of hashed G-trees.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+
+use arbitrary::Arbitrary;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::merkle::MerkleHasher;
+use crate::{insert, sets_assert_eq, ControlSet, GTree, NonemptySet, NonemptySetMeta, Set};
+
+/// A step of a conformance test. Items come with a flag that tells whether to give them a non-empty left subtree.
+///
+/// Steps that are invalid for the current set (such as inserting a minimum that is not less than all items) are skipped.
+#[derive(Debug, Arbitrary, Clone)]
+pub enum ConformanceOperation<Item> {
+    /// Replace the set by a singleton.
+    Singleton(Item, bool),
+    InsertMin(Item, bool),
+    RemoveMin,
+    /// Split the set at the item, and continue with the lesser part, or with the greater part if the flag is set.
+    Split(Item, bool),
+    /// Join the set with a set of those of the items that are greater than all items of the set.
+    JoinGreater(Vec<(Item, bool)>),
+    /// Join the set with a set of those of the items that are less than all items of the set.
+    JoinLesser(Vec<(Item, bool)>),
+    Search(Item),
+}
+
+/// Apply the operations to an initially empty set of type S and to a control set, and panic as soon as the two differ.
+pub fn check_conformance<S: NonemptySetMeta>(operations: &[ConformanceOperation<S::Item>])
+where
+    S::Item: Clone + Ord + Debug,
+{
+    let mut set: Set<S> = Set::Empty;
+    let mut ctrl: Set<ControlSet<S::Item>> = Set::Empty;
+
+    for operation in operations {
+        match operation {
+            ConformanceOperation::Singleton(item, labelled) => {
+                set = Set::NonEmpty(S::singleton(pair(item, *labelled)));
+                ctrl = Set::NonEmpty(ControlSet::singleton(pair(item, *labelled)));
+            }
+            ConformanceOperation::InsertMin(item, labelled) => match (&set, &ctrl) {
+                (Set::NonEmpty(s), Set::NonEmpty(c)) => {
+                    if item < c.get_min() {
+                        let ctrl_inserted = Set::NonEmpty(c.insert_min(pair(item, *labelled)));
+                        assert_conforms(&Set::NonEmpty(s.clone().insert_min_owned(pair(item, *labelled))), &ctrl_inserted);
+                        set = Set::NonEmpty(s.insert_min(pair(item, *labelled)));
+                        ctrl = ctrl_inserted;
+                    }
+                }
+                _ => {
+                    set = Set::NonEmpty(S::singleton(pair(item, *labelled)));
+                    ctrl = Set::NonEmpty(ControlSet::singleton(pair(item, *labelled)));
+                }
+            },
+            ConformanceOperation::RemoveMin => {
+                if let (Set::NonEmpty(s), Set::NonEmpty(c)) = (&set, &ctrl) {
+                    let ((min, subtree), rest) = s.remove_min();
+                    let ((ctrl_min, ctrl_subtree), ctrl_rest) = c.remove_min();
+                    assert_eq!(min, ctrl_min, "remove_min returned different items");
+                    assert_eq!(subtree_items(&subtree), subtree_items(&ctrl_subtree), "remove_min returned different left subtrees");
+
+                    let ((owned_min, owned_subtree), owned_rest) = s.clone().remove_min_owned();
+                    assert_eq!(owned_min, min, "remove_min_owned returned a different item than remove_min");
+                    assert_eq!(subtree_items(&owned_subtree), subtree_items(&subtree), "remove_min_owned returned a different left subtree than remove_min");
+                    assert_conforms(&owned_rest, &ctrl_rest);
+                    set = rest;
+                    ctrl = ctrl_rest;
+                }
+            }
+            ConformanceOperation::Split(key, continue_with_greater) => {
+                if let (Set::NonEmpty(s), Set::NonEmpty(c)) = (&set, &ctrl) {
+                    let (left, subtree, right) = s.split(key);
+                    let (ctrl_left, ctrl_subtree, ctrl_right) = c.split(key);
+                    assert_conforms(&left, &ctrl_left);
+                    assert_conforms(&right, &ctrl_right);
+                    assert_eq!(
+                        subtree.as_ref().map(subtree_items),
+                        ctrl_subtree.as_ref().map(subtree_items),
+                        "split returned different left subtrees for {:?}",
+                        key
+                    );
+
+                    let (owned_left, owned_found, owned_right) = s.clone().split_owned(key);
+                    assert_conforms(&owned_left, &ctrl_left);
+                    assert_conforms(&owned_right, &ctrl_right);
+                    assert_eq!(
+                        owned_found.map(|(item, subtree)| (item, subtree_items(&subtree))),
+                        c.search(key).filter(|(item, _)| item == key).map(|(item, subtree)| (item, subtree_items(&subtree))),
+                        "split_owned returned a different pair for {:?}",
+                        key
+                    );
+
+                    if *continue_with_greater {
+                        (set, ctrl) = (right, ctrl_right);
+                    } else {
+                        (set, ctrl) = (left, ctrl_left);
+                    }
+                }
+            }
+            ConformanceOperation::JoinGreater(items) => {
+                let items: Vec<_> = match &ctrl {
+                    Set::Empty => items.clone(),
+                    Set::NonEmpty(c) => items.iter().filter(|(item, _)| item > c.get_max()).cloned().collect(),
+                };
+                if let (Some(other), Some(ctrl_other)) = (from_items::<S>(&items), from_items::<ControlSet<S::Item>>(&items)) {
+                    (set, ctrl) = match (&set, &ctrl) {
+                        (Set::NonEmpty(s), Set::NonEmpty(c)) => {
+                            let joined = Set::NonEmpty(S::join(s, &other));
+                            let ctrl_joined = Set::NonEmpty(ControlSet::join(c, &ctrl_other));
+                            assert_conforms(&Set::NonEmpty(S::join_owned(s.clone(), other)), &ctrl_joined);
+                            (joined, ctrl_joined)
+                        }
+                        _ => (Set::NonEmpty(other), Set::NonEmpty(ctrl_other)),
+                    };
+                }
+            }
+            ConformanceOperation::JoinLesser(items) => {
+                let items: Vec<_> = match &ctrl {
+                    Set::Empty => items.clone(),
+                    Set::NonEmpty(c) => items.iter().filter(|(item, _)| item < c.get_min()).cloned().collect(),
+                };
+                if let (Some(other), Some(ctrl_other)) = (from_items::<S>(&items), from_items::<ControlSet<S::Item>>(&items)) {
+                    (set, ctrl) = match (&set, &ctrl) {
+                        (Set::NonEmpty(s), Set::NonEmpty(c)) => {
+                            let joined = Set::NonEmpty(S::join(&other, s));
+                            let ctrl_joined = Set::NonEmpty(ControlSet::join(&ctrl_other, c));
+                            assert_conforms(&Set::NonEmpty(S::join_owned(other, s.clone())), &ctrl_joined);
+                            (joined, ctrl_joined)
+                        }
+                        _ => (Set::NonEmpty(other), Set::NonEmpty(ctrl_other)),
+                    };
+                }
+            }
+            ConformanceOperation::Search(key) => {
+                if let (Set::NonEmpty(s), Set::NonEmpty(c)) = (&set, &ctrl) {
+                    let found = s.search(key).map(|(item, subtree)| (item, subtree_items(&subtree)));
+                    let ctrl_found = c.search(key).map(|(item, subtree)| (item, subtree_items(&subtree)));
+                    assert_eq!(found, ctrl_found, "search for {:?} returned different pairs", key);
+
+                    let found_ref = s.search_ref(key).map(|(item, subtree)| (item.clone(), subtree_items(subtree)));
+                    assert_eq!(found_ref, ctrl_found, "search_ref for {:?} returned a different pair than search", key);
+
+                    let found_subtree = s.search_left_subtree(key).map(|(subtree, equal)| (subtree_items(subtree), equal));
+                    let ctrl_found_subtree = ctrl_found.as_ref().map(|(item, subtree)| (subtree.clone(), item == key));
+                    assert_eq!(found_subtree, ctrl_found_subtree, "search_left_subtree for {:?} returned a different left subtree than search", key);
+                }
+            }
+        }
+
+        assert_conforms(&set, &ctrl);
+    }
+}
+
+/// Run `sequences` random sequences of operations through `check_conformance`, using an RNG seeded with `seed` and generating items with `random_item`.
+pub fn check_random_conformance<S: NonemptySetMeta, F: FnMut(&mut StdRng) -> S::Item>(seed: u64, sequences: usize, mut random_item: F)
+where
+    S::Item: Clone + Ord + Debug,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    for _ in 0..sequences {
+        let length = rng.gen_range(0..64);
+        let operations = random_operations(&mut rng, length, &mut random_item);
+        check_conformance::<S>(&operations);
+    }
+}
+
+/// Generate a random sequence of operations.
+pub fn random_operations<I, R: Rng, F: FnMut(&mut R) -> I>(rng: &mut R, length: usize, mut random_item: F) -> Vec<ConformanceOperation<I>> {
+    let mut operations = Vec::with_capacity(length);
+    for _ in 0..length {
+        let operation = match rng.gen_range(0..7) {
+            0 => ConformanceOperation::Singleton(random_item(rng), rng.gen()),
+            1 => ConformanceOperation::InsertMin(random_item(rng), rng.gen()),
+            2 => ConformanceOperation::RemoveMin,
+            3 => ConformanceOperation::Split(random_item(rng), rng.gen()),
+            4 => ConformanceOperation::JoinGreater((0..rng.gen_range(0..24)).map(|_| (random_item(rng), rng.gen())).collect()),
+            5 => ConformanceOperation::JoinLesser((0..rng.gen_range(0..24)).map(|_| (random_item(rng), rng.gen())).collect()),
+            _ => ConformanceOperation::Search(random_item(rng)),
+        };
+        operations.push(operation);
+    }
+    return operations;
+}
+
+/// Assert that a set contains the same pairs as a control set, comparing left subtrees by their items.
+pub fn assert_conforms<S: NonemptySetMeta>(set: &Set<S>, ctrl: &Set<ControlSet<S::Item>>)
+where
+    S::Item: Clone + Ord + Debug,
+{
+    match (set, ctrl) {
+        (Set::Empty, Set::Empty) => {}
+        (Set::NonEmpty(s), Set::NonEmpty(c)) => {
+            sets_assert_eq(s, c);
+            assert_eq!(s.get_min(), c.get_min(), "Comparing minima of the two sets.");
+            assert_eq!(s.get_max(), c.get_max(), "Comparing maxima of the two sets.");
+
+            let pairs: Vec<_> = s.pairs_descending().map(|(item, subtree)| (item.clone(), subtree_items(subtree))).collect();
+            let ctrl_pairs: Vec<_> = c.pairs_descending().map(|(item, subtree)| (item.clone(), subtree_items(subtree))).collect();
+            assert_eq!(pairs, ctrl_pairs, "Comparing the pairs of the two sets.");
+        }
+        _ => panic!("\n\nGot non-equal sets:\n\n{:#?}\n\n{:?}\n\n", set, ctrl),
+    }
+}
+
+// Internal helper function: a pair of an item and a left subtree, which contains just the item if `labelled` is set and is empty otherwise.
+fn pair<S: NonemptySetMeta>(item: &S::Item, labelled: bool) -> (S::Item, GTree<S>)
+where
+    S::Item: Clone,
+{
+    if labelled {
+        return (item.clone(), insert(&GTree::Empty, item.clone(), 0));
+    } else {
+        return (item.clone(), GTree::Empty);
+    }
+}
+
+// Internal helper function: create a set of the given items (deduplicated, in any order) via `singleton` and `insert_min`, or None if there are no items.
+fn from_items<S: NonemptySetMeta>(items: &[(S::Item, bool)]) -> Option<S>
+where
+    S::Item: Clone + Ord,
+{
+    let mut items = items.to_vec();
+    items.sort_by(|(a, _), (b, _)| b.cmp(a));
+    items.dedup_by(|(a, _), (b, _)| a == b);
+
+    let mut items = items.iter();
+    let (max, labelled) = items.next()?;
+    let mut set = S::singleton(pair(max, *labelled));
+    for (item, labelled) in items {
+        set = set.insert_min(pair(item, *labelled));
+    }
+    return Some(set);
+}
+
+// Internal helper function: the items of a left subtree.
+fn subtree_items<S: NonemptySet>(t: &GTree<S>) -> Vec<S::Item>
+where
+    S::Item: Clone,
+{
+    return t.iter().cloned().collect();
+}
+
+/// A simple Merkle hasher for testing, based on the standard library's `DefaultHasher`. Collisions are unlikely enough for the fuzzer not to find them.
+#[derive(Debug, Clone)]
+pub struct TestHasher;
+
+impl<Item: Hash> MerkleHasher<Item> for TestHasher {
+    type Digest = u64;
+
+    fn hash_node<'a, Pairs: Iterator<Item = (&'a Item, Option<&'a u64>)>>(rank: u8, pairs: Pairs, right: Option<&u64>) -> u64
+    where
+        Item: 'a,
+    {
+        let mut state = DefaultHasher::new();
+        rank.hash(&mut state);
+        for pair in pairs {
+            pair.hash(&mut state);
+        }
+        right.hash(&mut state);
+        return state.finish();
+    }
+}
@@ -0,0 +1,216 @@
+// This file implements a checker for the structural invariants of G-trees.
+//
+// `validate` walks the whole tree and reports the first violation it finds, together with the path from the root to the offending G-node. Unlike `gtree_stats`, it never prints anything, so it can be called after every single operation in fuzz targets and tests.
+
+use crate::augment::{summarize, Monoid, Summary};
+use crate::merkle::hash_node;
+use crate::{GTree, NonemptySetMeta};
+
+/// A step from a G-node to one of its subtrees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathStep<Item> {
+    /// Go to the left subtree of the given item.
+    Left(Item),
+    /// Go to the right subtree.
+    Right,
+}
+
+/// A violation of the invariants of a G-tree. The `path` leads from the root to the G-node at which the violation was detected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvariantViolation<Item> {
+    /// The inner set of the G-node contains no items.
+    EmptyInnerSet { path: Vec<PathStep<Item>> },
+    /// The left subtree of `item` has a rank greater than or equal to the rank of the G-node.
+    LeftSubtreeRankTooHigh {
+        path: Vec<PathStep<Item>>,
+        item: Item,
+        node_rank: u8,
+        subtree_rank: u8,
+    },
+    /// The right subtree has a greater rank than the G-node.
+    RightSubtreeRankTooHigh {
+        path: Vec<PathStep<Item>>,
+        node_rank: u8,
+        subtree_rank: u8,
+    },
+    /// The right subtree has the same rank as the G-node, even though the inner set of the G-node has free slots.
+    RightSubtreeRankWithFreeSlots { path: Vec<PathStep<Item>>, rank: u8 },
+    /// The item `lesser` should be strictly less than the item `greater`, but it is not.
+    OrderViolation {
+        path: Vec<PathStep<Item>>,
+        lesser: Item,
+        greater: Item,
+    },
+    /// The size cached in the G-node differs from the actual number of items in its tree.
+    WrongSize {
+        path: Vec<PathStep<Item>>,
+        cached: usize,
+        actual: usize,
+    },
+    /// The summary cached in the G-node differs from the combined summary of the items in its tree.
+    WrongSummary { path: Vec<PathStep<Item>> },
+    /// The hash cached in the G-node differs from the hash of its contents and the hashes cached in its subtrees.
+    WrongHash { path: Vec<PathStep<Item>> },
+}
+
+// What the checks of a G-node need to know about its (valid) subtrees.
+struct SubtreeInfo<Item> {
+    rank: Option<u8>, // None for the empty tree
+    least: Option<Item>,
+    greatest: Option<Item>,
+    size: usize,
+}
+
+/// Check all invariants of a G-tree: the heap property of the ranks, the search tree property of the items, non-empty inner sets, and the cached sizes, summaries and hashes. Checking the cached summaries requires comparing them.
+pub fn validate<S: NonemptySetMeta>(t: &GTree<S>) -> Result<(), InvariantViolation<S::Item>>
+where
+    S::Item: Clone + Ord,
+    Summary<S>: PartialEq,
+{
+    return validate_(t, &mut vec![]).map(|_| ());
+}
+
+fn validate_<S: NonemptySetMeta>(
+    t: &GTree<S>,
+    path: &mut Vec<PathStep<S::Item>>,
+) -> Result<SubtreeInfo<S::Item>, InvariantViolation<S::Item>>
+where
+    S::Item: Clone + Ord,
+    Summary<S>: PartialEq,
+{
+    match t {
+        GTree::Empty => {
+            return Ok(SubtreeInfo {
+                rank: None,
+                least: None,
+                greatest: None,
+                size: 0,
+            })
+        }
+        GTree::NonEmpty(node) => {
+            let mut lent: Vec<_> = node.set.pairs_descending_lent().collect();
+            lent.reverse();
+            let pairs: Vec<(&S::Item, &GTree<S>)> = lent.iter().map(|(item, left_subtree)| (&**item, *left_subtree)).collect();
+
+            if pairs.is_empty() {
+                return Err(InvariantViolation::EmptyInnerSet { path: path.clone() });
+            }
+
+            for window in pairs.windows(2) {
+                if window[0].0 >= window[1].0 {
+                    return Err(InvariantViolation::OrderViolation {
+                        path: path.clone(),
+                        lesser: window[0].0.clone(),
+                        greater: window[1].0.clone(),
+                    });
+                }
+            }
+
+            let mut size = 0;
+            let mut summary = Summary::<S>::neutral();
+            // The least item of the whole tree, if it lies in the left subtree of the least item of the set.
+            let mut least = None;
+            let mut previous_item: Option<&S::Item> = None;
+
+            for (item, left_subtree) in pairs.iter() {
+                path.push(PathStep::Left((*item).clone()));
+                let info = validate_(left_subtree, path)?;
+                path.pop();
+
+                if let Some(subtree_rank) = info.rank {
+                    if subtree_rank >= node.rank {
+                        return Err(InvariantViolation::LeftSubtreeRankTooHigh {
+                            path: path.clone(),
+                            item: (*item).clone(),
+                            node_rank: node.rank,
+                            subtree_rank,
+                        });
+                    }
+                }
+
+                // All items of the left subtree must lie between the previous item and `item`.
+                if let Some(greatest) = info.greatest {
+                    if &greatest >= *item {
+                        return Err(InvariantViolation::OrderViolation {
+                            path: path.clone(),
+                            lesser: greatest,
+                            greater: (*item).clone(),
+                        });
+                    }
+                }
+                match (previous_item, info.least) {
+                    (None, subtree_least) => least = subtree_least,
+                    (Some(previous_item), Some(subtree_least)) => {
+                        if &subtree_least <= previous_item {
+                            return Err(InvariantViolation::OrderViolation {
+                                path: path.clone(),
+                                lesser: previous_item.clone(),
+                                greater: subtree_least,
+                            });
+                        }
+                    }
+                    (Some(_), None) => {}
+                }
+
+                size += 1 + info.size;
+                summary = summary.combine(&left_subtree.summary()).combine(&summarize::<S>(item));
+                previous_item = Some(item);
+            }
+
+            path.push(PathStep::Right);
+            let right_info = validate_(&node.right, path)?;
+            path.pop();
+
+            if let Some(subtree_rank) = right_info.rank {
+                if subtree_rank > node.rank {
+                    return Err(InvariantViolation::RightSubtreeRankTooHigh {
+                        path: path.clone(),
+                        node_rank: node.rank,
+                        subtree_rank,
+                    });
+                } else if subtree_rank == node.rank && node.set.item_slot_count() > node.set.len() {
+                    return Err(InvariantViolation::RightSubtreeRankWithFreeSlots {
+                        path: path.clone(),
+                        rank: node.rank,
+                    });
+                }
+            }
+
+            let (greatest_item, _) = pairs[pairs.len() - 1];
+            if let Some(ref least) = right_info.least {
+                if least <= greatest_item {
+                    return Err(InvariantViolation::OrderViolation {
+                        path: path.clone(),
+                        lesser: greatest_item.clone(),
+                        greater: least.clone(),
+                    });
+                }
+            }
+
+            size += right_info.size;
+            if node.size != size {
+                return Err(InvariantViolation::WrongSize {
+                    path: path.clone(),
+                    cached: node.size,
+                    actual: size,
+                });
+            }
+
+            // The subtrees are valid, so their cached summaries and hashes are correct.
+            if node.summary != summary.combine(&node.right.summary()) {
+                return Err(InvariantViolation::WrongSummary { path: path.clone() });
+            }
+
+            if node.hash != hash_node(&node.set, &node.right, node.rank) {
+                return Err(InvariantViolation::WrongHash { path: path.clone() });
+            }
+
+            return Ok(SubtreeInfo {
+                rank: Some(node.rank),
+                least: Some(least.unwrap_or_else(|| pairs[0].0.clone())),
+                greatest: Some(right_info.greatest.unwrap_or_else(|| greatest_item.clone())),
+                size,
+            });
+        }
+    }
+}
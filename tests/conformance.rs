@@ -0,0 +1,55 @@
+// Run the conformance test suite (see `src/testing.rs`) against every inner set, for several K.
+
+use rand::{rngs::StdRng, Rng};
+
+use gtree_experiments::{array::*, klist::*, nested::*, packed::*, prefix::*, testing::*, ControlSet};
+
+const SEQUENCES: usize = 300;
+
+fn random_u8(rng: &mut StdRng) -> u8 {
+    return rng.gen();
+}
+
+// Short strings over a small alphabet, which share many prefixes.
+fn random_bytes(rng: &mut StdRng) -> Vec<u8> {
+    let length = rng.gen_range(0..6);
+    return (0..length).map(|_| rng.gen_range(b'a'..=b'c')).collect();
+}
+
+#[test]
+fn control_set() {
+    check_random_conformance::<ControlSet<u8>, _>(0, SEQUENCES, random_u8);
+}
+
+#[test]
+fn klist() {
+    check_random_conformance::<NonemptyReverseKList<1, u8>, _>(1, SEQUENCES, random_u8);
+    check_random_conformance::<NonemptyReverseKList<2, u8>, _>(2, SEQUENCES, random_u8);
+    check_random_conformance::<NonemptyReverseKList<3, u8>, _>(3, SEQUENCES, random_u8);
+    check_random_conformance::<NonemptyReverseKList<15, u8>, _>(4, SEQUENCES, random_u8);
+}
+
+#[test]
+fn array() {
+    check_random_conformance::<NonemptyReverseArray<1, u8>, _>(5, SEQUENCES, random_u8);
+    check_random_conformance::<NonemptyReverseArray<3, u8>, _>(6, SEQUENCES, random_u8);
+}
+
+#[test]
+fn nested() {
+    check_random_conformance::<NonemptyNestedSet<3, 2, u8>, _>(7, SEQUENCES, random_u8);
+    check_random_conformance::<NonemptyNestedSet<15, 4, u8>, _>(8, SEQUENCES, random_u8);
+}
+
+#[test]
+fn packed() {
+    check_random_conformance::<NonemptyPackedSet<3, u8>, _>(9, SEQUENCES, random_u8);
+    check_random_conformance::<NonemptyPackedSet<3, u64>, _>(10, SEQUENCES, |rng| rng.gen());
+}
+
+#[test]
+fn prefix() {
+    check_random_conformance::<NonemptyPrefixSet<3>, _>(11, SEQUENCES, random_bytes);
+    // Large enough that most sets consist of several blocks between restart items.
+    check_random_conformance::<NonemptyPrefixSet<63>, _>(12, SEQUENCES, random_bytes);
+}
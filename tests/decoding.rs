@@ -0,0 +1,83 @@
+// Test that packed and prefix sets decode their items into a cache only for the methods that lend out references to items, so that looking at a tree does not change how much memory it occupies.
+
+use std::{fmt::Debug, hash::Hash};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use gtree_experiments::algebra::union;
+use gtree_experiments::augment::Summary;
+use gtree_experiments::memory::memory_report;
+use gtree_experiments::packed::*;
+use gtree_experiments::prefix::*;
+use gtree_experiments::rank::{HashRank, RankDerivation};
+use gtree_experiments::validate::validate;
+use gtree_experiments::{delete, gtree_stats, has, insert, GTree, NonemptySetMeta};
+
+fn random_tree<S: NonemptySetMeta>(rng: &mut StdRng, n: usize, random_item: fn(&mut StdRng) -> S::Item) -> GTree<S>
+where
+    S::Item: Clone + Ord + Hash,
+{
+    let ranker = HashRank::new(3);
+    let mut t = GTree::Empty;
+    for _ in 0..n {
+        let item = random_item(rng);
+        let rank = ranker.rank(&item);
+        t = insert(&t, item, rank);
+    }
+    return t;
+}
+
+fn check_traversals_do_not_decode<S: NonemptySetMeta>(seed: u64, random_item: fn(&mut StdRng) -> S::Item)
+where
+    S::Item: Clone + Ord + Hash + Debug,
+    Summary<S>: PartialEq,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    let ranker = HashRank::new(3);
+    let mut t: GTree<S> = random_tree(&mut rng, 2000, random_item);
+    let before = memory_report(&t);
+
+    gtree_stats(&t);
+    assert!(validate(&t).is_ok());
+    let other = random_tree(&mut rng, 2000, random_item);
+    let other_before = memory_report(&other);
+    union(&t, &other);
+    assert_eq!(memory_report(&other), other_before, "the union decoded items");
+    for _ in 0..100 {
+        let key = random_item(&mut rng);
+        has(&t, &key);
+        t.count_less_than(&key);
+    }
+    assert_eq!(memory_report(&t), before, "looking at the tree decoded items");
+
+    // Updates create new G-nodes, which must not be decoded either.
+    for _ in 0..200 {
+        let item = random_item(&mut rng);
+        if rng.gen() {
+            let rank = ranker.rank(&item);
+            t = insert(&t, item, rank);
+        } else {
+            t = delete(&t, &item);
+        }
+    }
+    let updated = memory_report(&t);
+    assert!(validate(&t).is_ok());
+    assert_eq!(memory_report(&t), updated, "looking at the tree decoded items");
+
+    // Make sure that the memory report notices decoded items at all.
+    t.iter().for_each(drop);
+    assert!(memory_report(&t).set_heap_bytes > updated.set_heap_bytes, "iterating over the tree did not decode items");
+}
+
+#[test]
+fn packed_traversals_do_not_decode() {
+    check_traversals_do_not_decode::<NonemptyPackedSet<3, u32>>(0, |rng| rng.gen_range(0..100_000));
+}
+
+#[test]
+fn prefix_traversals_do_not_decode() {
+    check_traversals_do_not_decode::<NonemptyPrefixSet<3>>(1, |rng| {
+        let length = rng.gen_range(0..12);
+        return (0..length).map(|_| rng.gen_range(b'a'..=b'd')).collect();
+    });
+}
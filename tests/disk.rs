@@ -0,0 +1,159 @@
+// Test file-backed G-trees (see `src/disk.rs`) against a `BTreeSet`, including recovery from crashes, in a temporary directory.
+
+use std::collections::BTreeSet;
+use std::fs::{self, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use gtree_experiments::disk::*;
+use gtree_experiments::rank::{HashRank, RankDerivation};
+
+const K: usize = 4;
+
+type Store = DiskStore<K, u32>;
+
+// A fresh temporary directory for a test, which is removed when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("gtree_disk_{}_{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        return TempDir(path);
+    }
+
+    fn file(&self) -> PathBuf {
+        return self.0.join("tree");
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn insert(store: &mut Store, t: DiskTree, item: u32) -> DiskTree {
+    return store.insert(t, item, HashRank::new(K).rank(&item)).unwrap();
+}
+
+fn assert_contents(store: &Store, t: DiskTree, ctrl: &BTreeSet<u32>) {
+    assert_eq!(store.items(t).unwrap(), ctrl.iter().cloned().collect::<Vec<_>>());
+}
+
+#[test]
+fn random_updates_with_small_cache() {
+    let dir = TempDir::new("random");
+    let mut rng = StdRng::seed_from_u64(0);
+    // The tree grows to far more pages than the cache holds.
+    let mut store = Store::open(dir.file(), 16).unwrap();
+    let mut t = store.root();
+    let mut ctrl = BTreeSet::new();
+
+    for i in 0..3000 {
+        let item = rng.gen_range(0..2000);
+        if rng.gen_bool(0.7) {
+            t = insert(&mut store, t, item);
+            ctrl.insert(item);
+        } else {
+            t = store.delete(t, &item).unwrap();
+            ctrl.remove(&item);
+        }
+
+        let probe = rng.gen_range(0..2000);
+        assert_eq!(store.has(t, &probe).unwrap(), ctrl.contains(&probe));
+
+        if i % 500 == 0 {
+            store.commit(t).unwrap();
+        }
+    }
+    assert_contents(&store, t, &ctrl);
+    assert!(store.page_count() > 1000);
+
+    store.commit(t).unwrap();
+    drop(store);
+
+    let store = Store::open(dir.file(), 16).unwrap();
+    assert_contents(&store, store.root(), &ctrl);
+    for item in 0..2000 {
+        assert_eq!(store.has(store.root(), &item).unwrap(), ctrl.contains(&item));
+    }
+}
+
+#[test]
+fn old_trees_remain_intact() {
+    let dir = TempDir::new("persistent");
+    let mut store = Store::open(dir.file(), 64).unwrap();
+
+    let mut versions = vec![(DiskTree::empty(), BTreeSet::new())];
+    for item in 0..200 {
+        let (t, mut ctrl) = versions.last().unwrap().clone();
+        let t = if item % 3 == 2 {
+            ctrl.remove(&(item / 2));
+            store.delete(t, &(item / 2)).unwrap()
+        } else {
+            ctrl.insert(item);
+            insert(&mut store, t, item)
+        };
+        versions.push((t, ctrl));
+    }
+
+    for (t, ctrl) in versions.iter() {
+        assert_contents(&store, *t, ctrl);
+    }
+}
+
+#[test]
+fn recovers_last_commit_after_crash() {
+    let dir = TempDir::new("crash");
+    let mut store = Store::open(dir.file(), 64).unwrap();
+
+    let mut t = store.root();
+    let mut first = BTreeSet::new();
+    for item in 0..100 {
+        t = insert(&mut store, t, item);
+        first.insert(item);
+    }
+    store.commit(t).unwrap();
+
+    let mut second = first.clone();
+    for item in 100..200 {
+        t = insert(&mut store, t, item);
+        second.insert(item);
+    }
+    store.commit(t).unwrap();
+
+    // Updates that are never committed are lost.
+    for item in 200..300 {
+        t = insert(&mut store, t, item);
+    }
+    drop(store);
+
+    let mut store = Store::open(dir.file(), 64).unwrap();
+    assert_contents(&store, store.root(), &second);
+
+    // Updating the recovered tree overwrites the uncommitted pages.
+    let t = store.delete(store.root(), &150).unwrap();
+    second.remove(&150);
+    store.commit(t).unwrap();
+    drop(store);
+
+    let store = Store::open(dir.file(), 64).unwrap();
+    assert_contents(&store, store.root(), &second);
+    drop(store);
+
+    // A torn write of the newest commit record (in page 1, as this is the third commit) falls back to the previous commit.
+    let mut file = OpenOptions::new().write(true).open(dir.file()).unwrap();
+    file.seek(SeekFrom::Start(PAGE_SIZE as u64 + 20)).unwrap();
+    file.write_all(&[0xff; 8]).unwrap();
+    drop(file);
+
+    let store = Store::open(dir.file(), 64).unwrap();
+    second.insert(150);
+    assert_contents(&store, store.root(), &second);
+    assert!(store.has(store.root(), &150).unwrap());
+    assert!(!store.has(store.root(), &250).unwrap());
+}
@@ -0,0 +1,158 @@
+// Test the Graphviz rendering of G-trees (see `src/dot.rs`): the output must be well-formed, and G-nodes and vertices that are shared between trees must be drawn only once.
+
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use gtree_experiments::dot::*;
+use gtree_experiments::klist::*;
+use gtree_experiments::rank::{HashRank, RankDerivation};
+use gtree_experiments::{insert, GTree};
+
+const K: usize = 2;
+
+type Set = NonemptyReverseKList<K, u32>;
+
+fn random_tree(seed: u64, n: usize) -> GTree<Set> {
+    let ranker = HashRank::new(K);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut t = GTree::Empty;
+    for _ in 0..n {
+        let item = rng.gen_range(0..100_000);
+        t = insert(&t, item, ranker.rank(&item));
+    }
+    return t;
+}
+
+// A rendering, taken apart into the ids of the declared nodes (with how often each is declared) and the edges between them.
+struct Parsed {
+    declarations: HashMap<String, usize>,
+    edges: Vec<(String, String)>,
+}
+
+impl Parsed {
+    fn records(&self) -> usize {
+        return self.declarations.keys().filter(|id| id.starts_with('n')).count();
+    }
+
+    fn vertices(&self) -> usize {
+        return self.declarations.keys().filter(|id| id.starts_with('v')).count();
+    }
+}
+
+// Check that a rendering is well-formed DOT, as far as `to_dot_with` produces it: a single digraph with one statement per line, balanced braces in every label, and only edges between declared nodes.
+fn parse(dot: &str) -> Parsed {
+    let lines: Vec<&str> = dot.lines().collect();
+    assert_eq!(lines.first(), Some(&"digraph gtree {"));
+    assert_eq!(lines.last(), Some(&"}"));
+    assert!(dot.ends_with('\n'));
+
+    let mut declarations = HashMap::new();
+    let mut edges = vec![];
+    for line in &lines[1..lines.len() - 1] {
+        let statement = line.strip_prefix("    ").and_then(|line| line.strip_suffix(';'));
+        let statement = statement.unwrap_or_else(|| panic!("malformed statement {:?}", line));
+
+        // Braces in labels must be balanced, except for escaped ones.
+        let mut depth: i32 = 0;
+        let mut escaped = false;
+        for c in statement.chars() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            assert!(depth >= 0, "unbalanced braces in {:?}", line);
+        }
+        assert_eq!(depth, 0, "unbalanced braces in {:?}", line);
+        assert_eq!((statement.matches('"').count() - statement.matches("\\\"").count()) % 2, 0, "unbalanced quotes in {:?}", line);
+
+        let head = statement.split(" [").next().unwrap();
+        match head.split_once(" -> ") {
+            Some((from, to)) => {
+                let from = from.split(':').next().unwrap();
+                edges.push((from.to_string(), to.to_string()));
+            }
+            None => {
+                if head != "node" {
+                    *declarations.entry(head.to_string()).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    for (id, count) in &declarations {
+        assert_eq!(*count, 1, "{} was drawn {} times", id, count);
+    }
+    for (from, to) in &edges {
+        assert!(declarations.contains_key(from), "edge from undeclared node {}", from);
+        assert!(declarations.contains_key(to), "edge to undeclared node {}", to);
+    }
+    return Parsed { declarations, edges };
+}
+
+#[test]
+fn empty_tree() {
+    let parsed = parse(&to_dot(&GTree::<Set>::Empty));
+    assert_eq!(parsed.records(), 0);
+    assert_eq!(parsed.edges, vec![("tree0".to_string(), "tree0_empty".to_string())]);
+}
+
+#[test]
+fn single_node() {
+    let t = insert(&GTree::<Set>::Empty, 7, 0);
+    let dot = to_dot(&t);
+    let parsed = parse(&dot);
+    assert_eq!(parsed.records(), 1);
+    assert_eq!(parsed.edges.len(), 1);
+    assert!(dot.contains("{rank 0|{<i0> 7|<right> }}"), "{}", dot);
+
+    // The name of a tree is escaped.
+    let parsed = parse(&to_dot_with(&[("{t|\"0\"}", &t)], DotOptions::default()));
+    assert_eq!(parsed.records(), 1);
+}
+
+#[test]
+fn shared_nodes_are_drawn_once() {
+    let ranker = HashRank::new(K);
+    let old = random_tree(0, 1000);
+    let mut new = old.clone();
+    for item in 100_000..100_010 {
+        new = insert(&new, item, ranker.rank(&item));
+    }
+
+    let old_records = parse(&to_dot(&old)).records();
+    let new_records = parse(&to_dot(&new)).records();
+    let both = parse(&to_dot_with(&[("old", &old), ("new", &new)], DotOptions::default()));
+
+    // The new version copies only the G-nodes along the paths to the inserted items.
+    assert!(both.records() > old_records.max(new_records));
+    assert!(both.records() < old_records + new_records / 2);
+
+    // Drawing the same tree twice adds no G-nodes at all.
+    let twice = parse(&to_dot_with(&[("a", &old), ("b", &old)], DotOptions::default()));
+    assert_eq!(twice.records(), old_records);
+    assert_eq!(twice.edges.len(), parse(&to_dot(&old)).edges.len() + 1);
+}
+
+#[test]
+fn vertices_are_drawn_on_request() {
+    let old = random_tree(1, 1000);
+    let new = insert(&old, 100_000, 0);
+    let with_vertices = DotOptions { show_vertices: true };
+
+    assert_eq!(parse(&to_dot(&old)).vertices(), 0);
+    let old_parsed = parse(&to_dot_with(&[("old", &old)], with_vertices));
+    let total: usize = old.iter().count();
+    // Every item is shown in exactly one vertex, and vertices hold up to K items.
+    assert!(old_parsed.vertices() >= total / K);
+    assert!(old_parsed.vertices() <= total);
+    assert_eq!(old_parsed.edges.iter().filter(|(_, to)| to.starts_with('v')).count(), old_parsed.vertices());
+
+    // The chains of G-nodes that the insertion did not copy are shared between both versions.
+    let new_parsed = parse(&to_dot_with(&[("new", &new)], with_vertices));
+    let both = parse(&to_dot_with(&[("old", &old), ("new", &new)], with_vertices));
+    assert!(both.vertices() < old_parsed.vertices() + new_parsed.vertices() / 2);
+}
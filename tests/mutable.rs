@@ -0,0 +1,169 @@
+// Test that `insert_mut` and `remove_mut` modify uniquely owned G-trees in place, rather than copying the G-nodes above the part of the tree that changes.
+
+use std::cell::Cell;
+use std::collections::BTreeSet;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use gtree_experiments::klist::*;
+use gtree_experiments::rank::{HashRank, RankDerivation};
+use gtree_experiments::{GTree, GTreeNode, NonemptySet};
+
+const K: usize = 3;
+
+type Set = NonemptyReverseKList<K, u32>;
+
+thread_local! {
+    static CLONES: Cell<usize> = const { Cell::new(0) };
+}
+
+// An item that counts how often it is cloned. G-nodes that are taken apart rather than copied move their items instead of cloning them.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Counted(u32);
+
+impl Clone for Counted {
+    fn clone(&self) -> Self {
+        CLONES.with(|clones| clones.set(clones.get() + 1));
+        return Counted(self.0);
+    }
+}
+
+fn clones_during<F: FnOnce()>(f: F) -> usize {
+    let before = CLONES.with(|clones| clones.get());
+    f();
+    return CLONES.with(|clones| clones.get()) - before;
+}
+
+// The addresses of the G-nodes on the search path for `item` whose rank exceeds `rank`. Neither `insert_mut` nor `remove_mut` of an item of that rank changes the shape of the tree at these G-nodes, so they must stay where they are.
+fn path_above(t: &GTree<Set>, item: u32, rank: u8) -> Vec<*const GTreeNode<Set>> {
+    let mut path = vec![];
+    let mut t = t;
+    while let GTree::NonEmpty(node) = t {
+        if node.rank() <= rank {
+            break;
+        }
+        path.push(&**node as *const GTreeNode<Set>);
+        match node.set().search_left_subtree(&item) {
+            None => t = node.right(),
+            Some((_, true)) => break,
+            Some((left_subtree, false)) => t = left_subtree,
+        }
+    }
+    return path;
+}
+
+#[test]
+fn unshared_paths_are_not_copied() {
+    let ranker = HashRank::new(K);
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut t = GTree::Empty;
+    let mut ctrl = BTreeSet::new();
+
+    for _ in 0..2000 {
+        let item = rng.gen_range(0..100_000);
+        t.insert_mut(item, ranker.rank(&item));
+        ctrl.insert(item);
+    }
+
+    let mut checked_nodes = 0;
+
+    for _ in 0..200 {
+        let item = rng.gen_range(0..100_000);
+        if ctrl.contains(&item) {
+            continue;
+        }
+        let rank = ranker.rank(&item);
+        let before = path_above(&t, item, rank);
+        assert!(t.insert_mut(item, rank));
+        ctrl.insert(item);
+        assert_eq!(path_above(&t, item, rank), before, "insert_mut copied a G-node above the one that changed");
+        checked_nodes += before.len();
+    }
+
+    let present: Vec<_> = ctrl.iter().cloned().step_by(10).collect();
+    for item in present {
+        let rank = ranker.rank(&item);
+        let before = path_above(&t, item, rank);
+        assert!(t.remove_mut(&item));
+        ctrl.remove(&item);
+        assert_eq!(path_above(&t, item, rank), before, "remove_mut copied a G-node above the one that changed");
+        checked_nodes += before.len();
+    }
+
+    // Make sure that the paths were not trivially short.
+    assert!(checked_nodes > 1000);
+    assert_eq!(t.iter().cloned().collect::<Vec<_>>(), ctrl.iter().cloned().collect::<Vec<_>>());
+}
+
+#[test]
+fn unshared_nodes_are_taken_apart() {
+    let ranker = HashRank::new(K);
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut t: GTree<NonemptyReverseKList<K, Counted>> = GTree::Empty;
+
+    for _ in 0..2000 {
+        let item = rng.gen_range(0..100_000);
+        t.insert_mut(Counted(item), ranker.rank(&item));
+    }
+
+    // Apply the same updates to a tree all of whose G-nodes are shared, which must be copied, and to the unshared tree itself.
+    let mut shared_clones = 0;
+    let mut unshared_clones = 0;
+
+    for _ in 0..200 {
+        let item = rng.gen_range(0..100_000);
+        let rank = ranker.rank(&item);
+        let mut shared = t.clone();
+        shared_clones += clones_during(|| {
+            shared.insert_mut(Counted(item), rank);
+        });
+        drop(shared);
+        unshared_clones += clones_during(|| {
+            t.insert_mut(Counted(item), rank);
+        });
+    }
+
+    for _ in 0..200 {
+        let item = Counted(rng.gen_range(0..100_000));
+        let mut shared = t.clone();
+        shared_clones += clones_during(|| {
+            shared.remove_mut(&item);
+        });
+        drop(shared);
+        unshared_clones += clones_during(|| {
+            t.remove_mut(&item);
+        });
+    }
+
+    assert!(
+        unshared_clones * 3 < shared_clones * 2,
+        "updating the unshared tree cloned {} items, updating the shared one {}",
+        unshared_clones,
+        shared_clones
+    );
+}
+
+#[test]
+fn unshared_lists_are_not_copied() {
+    // A long list whose vertices are all uniquely owned. Jump pointers must not count as owners, or every vertex would look shared.
+    let pairs = (0..1000).rev().map(|item| (Counted(item * 2 + 1), GTree::Empty)).collect();
+    let list = NonemptyReverseKList::<K, Counted>::from_descending_pairs(pairs);
+
+    let mut list = Some(list);
+    let clones = clones_during(|| {
+        list = Some(list.take().unwrap().insert_min_owned((Counted(0), GTree::Empty)));
+    });
+    assert_eq!(clones, 0, "insert_min_owned copied vertices of an unshared list");
+
+    let list = list.unwrap();
+    let shared = list.clone();
+    assert!(clones_during(|| drop(shared.insert_min_owned((Counted(0), GTree::Empty)))) > 0);
+
+    let mut parts = None;
+    let clones = clones_during(|| {
+        parts = Some(list.split_owned(&Counted(1001)));
+    });
+    assert_eq!(clones, 0, "split_owned copied vertices of an unshared list");
+    let (_, found, _) = parts.unwrap();
+    assert!(found.is_some_and(|(item, _)| item == Counted(1001)));
+}